@@ -0,0 +1,69 @@
+//! Serde helpers for `BigDecimal` amounts, which Yandex.Money returns either as JSON strings or as JSON numbers.
+
+use bigdecimal::BigDecimal;
+use serde::{de, Deserializer, Serializer};
+use std::{fmt, str::FromStr};
+
+struct Visitor;
+
+impl<'de> de::Visitor<'de> for Visitor {
+    type Value = BigDecimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a decimal number or a string containing one")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        BigDecimal::from_str(v).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(BigDecimal::from(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(BigDecimal::from(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        // Shortest representation of the float, so that 100.5 does not become 100.4999...
+        self.visit_str(&v.to_string())
+    }
+}
+
+#[allow(clippy::missing_errors_doc)]
+pub fn serialize<S: Serializer>(value: &BigDecimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+#[allow(clippy::missing_errors_doc)]
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigDecimal, D::Error> {
+    deserializer.deserialize_any(Visitor)
+}
+
+/// Same as the parent module, for optional amounts. Use together with `#[serde(default)]`.
+pub mod option {
+    use bigdecimal::BigDecimal;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct Flexible(#[serde(with = "crate::bigdecimal_flexible")] BigDecimal);
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn serialize<S: Serializer>(
+        value: &Option<BigDecimal>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<BigDecimal>, D::Error> {
+        Ok(Option::<Flexible>::deserialize(deserializer)?.map(|Flexible(value)| value))
+    }
+}
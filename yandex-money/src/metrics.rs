@@ -0,0 +1,195 @@
+//! Prometheus metrics for payment processing.
+
+use crate::*;
+use prometheus::{
+    register_counter, register_histogram, register_int_counter_vec, register_int_gauge, Counter,
+    Histogram, IntCounterVec, IntGauge,
+};
+
+#[derive(Clone, Debug)]
+pub struct PaymentMetrics {
+    pub attempts: Counter,
+    pub successes: Counter,
+    pub failures: IntCounterVec,
+    pub latency: Histogram,
+    pub in_flight: IntGauge,
+}
+
+impl PaymentMetrics {
+    /// Registers payment metrics in the default Prometheus registry.
+    ///
+    /// # Panics
+    /// Panics if metrics with the same names are already registered.
+    #[must_use]
+    pub fn register() -> Self {
+        Self {
+            attempts: register_counter!(
+                "yandex_money_payment_attempts_total",
+                "Number of process_payment calls"
+            )
+            .expect("failed to register payment attempts counter"),
+            successes: register_counter!(
+                "yandex_money_payment_successes_total",
+                "Number of successfully processed payments"
+            )
+            .expect("failed to register payment successes counter"),
+            failures: register_int_counter_vec!(
+                "yandex_money_payment_failures_total",
+                "Number of failed payments by error code",
+                &["code"]
+            )
+            .expect("failed to register payment failures counter"),
+            latency: register_histogram!(
+                "yandex_money_payment_latency_seconds",
+                "Latency of process_payment calls"
+            )
+            .expect("failed to register payment latency histogram"),
+            in_flight: register_int_gauge!(
+                "yandex_money_payments_in_flight",
+                "Number of process_payment calls in progress"
+            )
+            .expect("failed to register in-flight payments gauge"),
+        }
+    }
+
+    fn record(&self, res: &YMResult<ProcessPaymentResponse>) {
+        let failure_code = match res {
+            Ok(ProcessPaymentResponse::Success(_)) => {
+                self.successes.inc();
+                return;
+            }
+            Ok(ProcessPaymentResponse::Refused { error }) => error.clone(),
+            Ok(ProcessPaymentResponse::AccountBlocked { .. }) => "account_blocked".into(),
+            // Payment is not finished yet
+            Ok(ProcessPaymentResponse::InProgress { .. })
+            | Ok(ProcessPaymentResponse::ExtAuthRequired { .. }) => return,
+            Err(e) => e
+                .yandex_code()
+                .map_or_else(|| "transport_error".into(), |code| code.to_string()),
+        };
+
+        self.failures.with_label_values(&[&failure_code]).inc();
+    }
+}
+
+/// Client that records payment metrics on each `process_payment` call.
+pub struct MetricsClient {
+    inner: Client,
+    metrics: PaymentMetrics,
+}
+
+impl MetricsClient {
+    #[must_use]
+    pub const fn metrics(&self) -> &PaymentMetrics {
+        &self.metrics
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> Client {
+        self.inner
+    }
+}
+
+impl Client {
+    #[must_use]
+    pub const fn with_metrics(self, metrics: PaymentMetrics) -> MetricsClient {
+        MetricsClient {
+            inner: self,
+            metrics,
+        }
+    }
+}
+
+#[async_trait]
+impl API for MetricsClient {
+    async fn account_info(&self) -> YMResult<AccountInfo> {
+        self.inner.account_info().await
+    }
+
+    async fn cards_list(&self) -> YMResult<CardsListResponse> {
+        self.inner.cards_list().await
+    }
+
+    fn operation_history_with_params(
+        &self,
+        params: OperationHistoryParams,
+    ) -> OperationHistoryStream {
+        self.inner.operation_history_with_params(params)
+    }
+
+    async fn operation_details(&self, operation_id: String) -> YMResult<OperationDetails> {
+        self.inner.operation_details(operation_id).await
+    }
+
+    async fn incoming_transfer_accept(
+        &self,
+        operation_id: String,
+        protection_code: Option<String>,
+    ) -> YMResult<IncomingTransferAcceptResponse> {
+        self.inner
+            .incoming_transfer_accept(operation_id, protection_code)
+            .await
+    }
+
+    async fn incoming_transfer_reject(&self, operation_id: String) -> YMResult<()> {
+        self.inner.incoming_transfer_reject(operation_id).await
+    }
+
+    fn request_shop_payment(
+        &self,
+        pattern_id: String,
+        other: HashMap<String, String>,
+    ) -> PaymentRequest {
+        self.inner.request_shop_payment(pattern_id, other)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn request_transfer(
+        &self,
+        to: UserId,
+        amount: RequestAmount,
+        comment: String,
+        message: String,
+        label: Option<String>,
+        codepro: bool,
+        hold_for_pickup: bool,
+        expire_period: u32,
+    ) -> PaymentRequest {
+        self.inner.request_transfer(
+            to,
+            amount,
+            comment,
+            message,
+            label,
+            codepro,
+            hold_for_pickup,
+            expire_period,
+        )
+    }
+
+    fn request_mobile_payment(
+        &self,
+        phone_number: PhoneNumber,
+        amount: BigDecimal,
+    ) -> PaymentRequest {
+        self.inner.request_mobile_payment(phone_number, amount)
+    }
+
+    async fn process_payment(
+        &self,
+        request_id: String,
+        money_source: ProcessPaymentMoneySource,
+    ) -> YMResult<ProcessPaymentResponse> {
+        self.metrics.attempts.inc();
+        self.metrics.in_flight.inc();
+        let timer = self.metrics.latency.start_timer();
+
+        let res = self.inner.process_payment(request_id, money_source).await;
+
+        timer.observe_duration();
+        self.metrics.in_flight.dec();
+        self.metrics.record(&res);
+
+        res
+    }
+}
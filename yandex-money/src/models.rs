@@ -1,12 +1,18 @@
+use crate::transport::SecureString;
 use bigdecimal::BigDecimal;
 use chrono::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
     fmt::{Debug, Display},
+    iter::FromIterator,
+    ops::RangeInclusive,
     str::FromStr,
 };
 use strum::*;
+use url::Url;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AccessScope {
@@ -18,12 +24,347 @@ pub enum AccessScope {
     PaymentP2P,
 }
 
+/// Set of scopes requested by `UnauthorizedClient::authorize`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AccessScopeSet(HashSet<AccessScope>);
+
+impl AccessScopeSet {
+    /// Every scope supported by the library.
+    #[must_use]
+    pub fn all() -> Self {
+        vec![
+            AccessScope::AccountInfo,
+            AccessScope::OperationHistory,
+            AccessScope::PaymentP2P,
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    /// Only `AccessScope::AccountInfo`, enough to read balance and account number.
+    #[must_use]
+    pub fn minimal() -> Self {
+        Self::default().insert(AccessScope::AccountInfo)
+    }
+
+    #[must_use]
+    pub fn insert(mut self, scope: AccessScope) -> Self {
+        self.0.insert(scope);
+        self
+    }
+
+    #[must_use]
+    pub fn remove(mut self, scope: AccessScope) -> Self {
+        self.0.remove(&scope);
+        self
+    }
+
+    #[must_use]
+    pub fn contains(&self, scope: AccessScope) -> bool {
+        self.0.contains(&scope)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = AccessScope> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl From<HashSet<AccessScope>> for AccessScopeSet {
+    fn from(scopes: HashSet<AccessScope>) -> Self {
+        Self(scopes)
+    }
+}
+
+impl FromIterator<AccessScope> for AccessScopeSet {
+    fn from_iter<I: IntoIterator<Item = AccessScope>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for AccessScopeSet {
+    type Item = AccessScope;
+    type IntoIter = std::collections::hash_set::IntoIter<AccessScope>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Error code returned by Yandex.Money in the `error` field.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum YandexErrorCode {
+    IllegalParams,
+    IllegalParamLabel,
+    IllegalParamTo,
+    IllegalParamAmount,
+    IllegalParamAmountDue,
+    IllegalParamComment,
+    IllegalParamMessage,
+    IllegalParamExpirePeriod,
+    IllegalParamCsc,
+    IllegalParamExtAuthSuccessUri,
+    IllegalParamExtAuthFailUri,
+    IllegalParamOperationId,
+    IllegalParamType,
+    IllegalParamStartRecord,
+    IllegalParamRecords,
+    IllegalParamFrom,
+    IllegalParamTill,
+    NotEnoughFunds,
+    PaymentRefused,
+    PayeeNotFound,
+    AuthorizationReject,
+    LimitExceeded,
+    AccountBlocked,
+    ExtActionRequired,
+    ContractNotFound,
+    MoneySourceNotAvailable,
+    TechnicalError,
+    InvalidRequest,
+    UnauthorizedClient,
+    InvalidGrant,
+    InvalidScope,
+    Unauthorized,
+    InvalidToken,
+    IllegalParamToken,
+    Unknown(String),
+}
+
+impl YandexErrorCode {
+    /// Whether the request may succeed if repeated later.
+    #[must_use]
+    pub const fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::LimitExceeded | Self::TechnicalError | Self::Unknown(_)
+        )
+    }
+
+    /// Whether the access token is missing, expired or revoked, and a new one has to be obtained.
+    #[must_use]
+    pub const fn is_auth_failure(&self) -> bool {
+        matches!(
+            self,
+            Self::Unauthorized | Self::InvalidToken | Self::IllegalParamToken
+        )
+    }
+
+    /// Whether repeating the request will always fail.
+    #[must_use]
+    pub const fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Self::PaymentRefused
+                | Self::IllegalParams
+                | Self::IllegalParamLabel
+                | Self::IllegalParamTo
+                | Self::IllegalParamAmount
+                | Self::IllegalParamAmountDue
+                | Self::IllegalParamComment
+                | Self::IllegalParamMessage
+                | Self::IllegalParamExpirePeriod
+                | Self::IllegalParamCsc
+                | Self::IllegalParamExtAuthSuccessUri
+                | Self::IllegalParamExtAuthFailUri
+                | Self::IllegalParamOperationId
+                | Self::IllegalParamType
+                | Self::IllegalParamStartRecord
+                | Self::IllegalParamRecords
+                | Self::IllegalParamFrom
+                | Self::IllegalParamTill
+                | Self::AccountBlocked
+        )
+    }
+
+    /// HTTP status to respond with when forwarding the error to downstream clients.
+    #[cfg(feature = "http-status")]
+    #[must_use]
+    pub fn to_http_status(&self) -> http::StatusCode {
+        use http::StatusCode;
+
+        match self {
+            Self::IllegalParams
+            | Self::IllegalParamLabel
+            | Self::IllegalParamTo
+            | Self::IllegalParamAmount
+            | Self::IllegalParamAmountDue
+            | Self::IllegalParamComment
+            | Self::IllegalParamMessage
+            | Self::IllegalParamExpirePeriod
+            | Self::IllegalParamCsc
+            | Self::IllegalParamExtAuthSuccessUri
+            | Self::IllegalParamExtAuthFailUri
+            | Self::IllegalParamOperationId
+            | Self::IllegalParamType
+            | Self::IllegalParamStartRecord
+            | Self::IllegalParamRecords
+            | Self::IllegalParamFrom
+            | Self::IllegalParamTill
+            | Self::MoneySourceNotAvailable => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::NotEnoughFunds | Self::PaymentRefused => StatusCode::PAYMENT_REQUIRED,
+            Self::AuthorizationReject
+            | Self::UnauthorizedClient
+            | Self::Unauthorized
+            | Self::InvalidToken
+            | Self::IllegalParamToken => StatusCode::UNAUTHORIZED,
+            Self::AccountBlocked | Self::ExtActionRequired | Self::InvalidScope => {
+                StatusCode::FORBIDDEN
+            }
+            Self::PayeeNotFound | Self::ContractNotFound => StatusCode::NOT_FOUND,
+            Self::LimitExceeded => StatusCode::TOO_MANY_REQUESTS,
+            Self::InvalidRequest | Self::InvalidGrant => StatusCode::BAD_REQUEST,
+            Self::TechnicalError => StatusCode::BAD_GATEWAY,
+            Self::Unknown(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Human-readable description of the error.
+    #[must_use]
+    pub fn describe(&self) -> &str {
+        match self {
+            Self::IllegalParams => "invalid payment parameters",
+            Self::IllegalParamLabel => "invalid label",
+            Self::IllegalParamTo => "invalid recipient",
+            Self::IllegalParamAmount => "invalid amount",
+            Self::IllegalParamAmountDue => "invalid amount due",
+            Self::IllegalParamComment => "invalid comment",
+            Self::IllegalParamMessage => "invalid message",
+            Self::IllegalParamExpirePeriod => "invalid expiration period",
+            Self::IllegalParamCsc => "invalid card security code",
+            Self::IllegalParamExtAuthSuccessUri => "invalid external authentication success URI",
+            Self::IllegalParamExtAuthFailUri => "invalid external authentication failure URI",
+            Self::IllegalParamOperationId => "invalid operation ID",
+            Self::IllegalParamType => "invalid operation type",
+            Self::IllegalParamStartRecord => "invalid start record",
+            Self::IllegalParamRecords => "invalid number of records",
+            Self::IllegalParamFrom => "invalid start date",
+            Self::IllegalParamTill => "invalid end date",
+            Self::NotEnoughFunds => "not enough funds",
+            Self::PaymentRefused => "payment refused",
+            Self::PayeeNotFound => "recipient not found",
+            Self::AuthorizationReject => "authorization rejected",
+            Self::LimitExceeded => "limit exceeded",
+            Self::AccountBlocked => "account blocked",
+            Self::ExtActionRequired => "action required on the Yandex.Money website",
+            Self::ContractNotFound => "payment request not found",
+            Self::MoneySourceNotAvailable => "money source not available",
+            Self::TechnicalError => "technical error, try again later",
+            Self::InvalidRequest => "invalid request",
+            Self::UnauthorizedClient => "client is not authorized",
+            Self::InvalidGrant => "authorization code is invalid or expired",
+            Self::InvalidScope => "invalid access scope",
+            Self::Unauthorized => "access token is missing",
+            Self::InvalidToken => "access token is invalid, expired or revoked",
+            Self::IllegalParamToken => "malformed access token",
+            Self::Unknown(code) => code,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::IllegalParams => "illegal_params",
+            Self::IllegalParamLabel => "illegal_param_label",
+            Self::IllegalParamTo => "illegal_param_to",
+            Self::IllegalParamAmount => "illegal_param_amount",
+            Self::IllegalParamAmountDue => "illegal_param_amount_due",
+            Self::IllegalParamComment => "illegal_param_comment",
+            Self::IllegalParamMessage => "illegal_param_message",
+            Self::IllegalParamExpirePeriod => "illegal_param_expire_period",
+            Self::IllegalParamCsc => "illegal_param_csc",
+            Self::IllegalParamExtAuthSuccessUri => "illegal_param_ext_auth_success_uri",
+            Self::IllegalParamExtAuthFailUri => "illegal_param_ext_auth_fail_uri",
+            Self::IllegalParamOperationId => "illegal_param_operation_id",
+            Self::IllegalParamType => "illegal_param_type",
+            Self::IllegalParamStartRecord => "illegal_param_start_record",
+            Self::IllegalParamRecords => "illegal_param_records",
+            Self::IllegalParamFrom => "illegal_param_from",
+            Self::IllegalParamTill => "illegal_param_till",
+            Self::NotEnoughFunds => "not_enough_funds",
+            Self::PaymentRefused => "payment_refused",
+            Self::PayeeNotFound => "payee_not_found",
+            Self::AuthorizationReject => "authorization_reject",
+            Self::LimitExceeded => "limit_exceeded",
+            Self::AccountBlocked => "account_blocked",
+            Self::ExtActionRequired => "ext_action_required",
+            Self::ContractNotFound => "contract_not_found",
+            Self::MoneySourceNotAvailable => "money_source_not_available",
+            Self::TechnicalError => "technical_error",
+            Self::InvalidRequest => "invalid_request",
+            Self::UnauthorizedClient => "unauthorized_client",
+            Self::InvalidGrant => "invalid_grant",
+            Self::InvalidScope => "invalid_scope",
+            Self::Unauthorized => "unauthorized",
+            Self::InvalidToken => "invalid_token",
+            Self::IllegalParamToken => "illegal_param_token",
+            Self::Unknown(code) => code,
+        }
+    }
+}
+
+impl From<&str> for YandexErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "illegal_params" => Self::IllegalParams,
+            "illegal_param_label" => Self::IllegalParamLabel,
+            "illegal_param_to" => Self::IllegalParamTo,
+            "illegal_param_amount" => Self::IllegalParamAmount,
+            "illegal_param_amount_due" => Self::IllegalParamAmountDue,
+            "illegal_param_comment" => Self::IllegalParamComment,
+            "illegal_param_message" => Self::IllegalParamMessage,
+            "illegal_param_expire_period" => Self::IllegalParamExpirePeriod,
+            "illegal_param_csc" => Self::IllegalParamCsc,
+            "illegal_param_ext_auth_success_uri" => Self::IllegalParamExtAuthSuccessUri,
+            "illegal_param_ext_auth_fail_uri" => Self::IllegalParamExtAuthFailUri,
+            "illegal_param_operation_id" => Self::IllegalParamOperationId,
+            "illegal_param_type" => Self::IllegalParamType,
+            "illegal_param_start_record" => Self::IllegalParamStartRecord,
+            "illegal_param_records" => Self::IllegalParamRecords,
+            "illegal_param_from" => Self::IllegalParamFrom,
+            "illegal_param_till" => Self::IllegalParamTill,
+            "not_enough_funds" => Self::NotEnoughFunds,
+            "payment_refused" => Self::PaymentRefused,
+            "payee_not_found" => Self::PayeeNotFound,
+            "authorization_reject" => Self::AuthorizationReject,
+            "limit_exceeded" => Self::LimitExceeded,
+            "account_blocked" => Self::AccountBlocked,
+            "ext_action_required" => Self::ExtActionRequired,
+            "contract_not_found" => Self::ContractNotFound,
+            "money_source_not_available" => Self::MoneySourceNotAvailable,
+            "technical_error" => Self::TechnicalError,
+            "invalid_request" => Self::InvalidRequest,
+            "unauthorized_client" => Self::UnauthorizedClient,
+            "invalid_grant" => Self::InvalidGrant,
+            "invalid_scope" => Self::InvalidScope,
+            "unauthorized" => Self::Unauthorized,
+            "invalid_token" => Self::InvalidToken,
+            "illegal_param_token" => Self::IllegalParamToken,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Display for YandexErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TokenExchangeData {
     pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AccountStatus {
     Anonymous,
@@ -31,6 +372,8 @@ pub enum AccountStatus {
     Identified,
 }
 
+pub type VerificationLevel = AccountStatus;
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AccountType {
@@ -40,20 +383,30 @@ pub enum AccountType {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BalanceDetails {
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub total: BigDecimal,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub available: BigDecimal,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub deposition_pending: BigDecimal,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub blocked: BigDecimal,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub debt: BigDecimal,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub hold: BigDecimal,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CardType {
     VISA,
     MasterCard,
     AmericanExpress,
     JCB,
+    Maestro,
+    Mir,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -64,9 +417,26 @@ pub struct LinkedCard {
     pub card_type: Option<CardType>,
 }
 
+/// Bank card linked to the account, see `API::cards_list`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardInfo {
+    pub id: String,
+    pub pan_fragment: String,
+    #[serde(rename = "type")]
+    pub card_type: CardType,
+    #[serde(default)]
+    pub expire: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardsListResponse {
+    pub cards: Vec<CardInfo>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AccountInfo {
     pub account: String,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub balance: BigDecimal,
     pub currency: String,
     pub account_status: AccountStatus,
@@ -74,6 +444,71 @@ pub struct AccountInfo {
     #[serde(default)]
     pub balance_details: Option<BalanceDetails>,
     pub cards_linked: Vec<LinkedCard>,
+    #[serde(default)]
+    pub avatar: Option<Avatar>,
+}
+
+/// Profile photo of the account.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Avatar {
+    pub url: String,
+    /// Time the photo was last changed.
+    pub ts: DateTime<Utc>,
+}
+
+impl AccountInfo {
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("AccountInfo is always serializable")
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// Parsed `avatar` link, `None` if the account has no avatar or the link is malformed.
+    #[must_use]
+    pub fn avatar_url(&self) -> Option<Url> {
+        Url::parse(&self.avatar.as_ref()?.url).ok()
+    }
+
+    #[must_use]
+    pub fn is_verified(&self) -> bool {
+        self.account_status == AccountStatus::Identified
+    }
+
+    #[must_use]
+    pub const fn verification_level(&self) -> VerificationLevel {
+        self.account_status
+    }
+
+    /// Maximum amount of a single payment in rubles for account's verification level.
+    #[must_use]
+    pub fn max_payment(&self) -> BigDecimal {
+        BigDecimal::from(match self.verification_level() {
+            VerificationLevel::Anonymous => 15_000,
+            VerificationLevel::Named => 60_000,
+            VerificationLevel::Identified => 250_000,
+        })
+    }
+
+    /// Documented limit of a single payment. Limits of professional accounts are set individually and not known.
+    #[must_use]
+    pub fn payment_limit_per_transaction(&self) -> Option<BigDecimal> {
+        match self.account_type {
+            AccountType::Personal => Some(self.max_payment()),
+            AccountType::Professional => None,
+        }
+    }
+
+    /// Whether `amount` is within the payment limit and available balance.
+    #[must_use]
+    pub fn can_send_amount(&self, amount: &BigDecimal) -> bool {
+        self.payment_limit_per_transaction()
+            .map_or(true, |limit| *amount <= limit)
+            && *amount <= self.balance
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -96,9 +531,9 @@ pub enum ReqOperationType {
     IncomingTransfersUnaccepted,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-pub enum RspOperationType {
+pub enum OperationType {
     PaymentShop,
     OutgoingTransfer,
     Deposition,
@@ -106,6 +541,230 @@ pub enum RspOperationType {
     IncomingTransferProtected,
 }
 
+pub type RspOperationType = OperationType;
+
+impl From<ReqOperationType> for OperationType {
+    fn from(value: ReqOperationType) -> Self {
+        match value {
+            ReqOperationType::Deposition => Self::Deposition,
+            ReqOperationType::Payment => Self::PaymentShop,
+            ReqOperationType::IncomingTransfersUnaccepted => Self::IncomingTransferProtected,
+        }
+    }
+}
+
+impl TryFrom<OperationType> for ReqOperationType {
+    type Error = OperationType;
+
+    fn try_from(value: OperationType) -> Result<Self, Self::Error> {
+        match value {
+            OperationType::Deposition => Ok(Self::Deposition),
+            OperationType::PaymentShop | OperationType::OutgoingTransfer => Ok(Self::Payment),
+            OperationType::IncomingTransferProtected => Ok(Self::IncomingTransfersUnaccepted),
+            // No dedicated history filter for accepted incoming transfers
+            OperationType::IncomingTransfer => Err(value),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OperationHistoryParams {
+    pub operation_types: HashSet<ReqOperationType>,
+    pub label: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub till: Option<DateTime<Utc>>,
+    pub start_record: u64,
+    /// Number of records in a page.
+    pub records: Option<u64>,
+    /// Total number of records to fetch across all pages, see `record_limit`.
+    pub record_limit: Option<usize>,
+    pub details: bool,
+    /// Raw parameters sent along with the typed ones, see `with_custom_param`.
+    pub extra_params: HashMap<String, String>,
+}
+
+/// Request parameter names set from typed fields of `OperationHistoryParams`.
+const TYPED_HISTORY_PARAMS: &[&str] = &[
+    "types",
+    "label",
+    "from",
+    "till",
+    "start-record",
+    "records",
+    "details",
+];
+
+/// Builder of `API::operation_history_query` requests.
+pub type OperationHistoryRequest = OperationHistoryParams;
+
+impl OperationHistoryParams {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only operations of these types, all operations if empty.
+    #[must_use]
+    pub fn types(mut self, types: HashSet<ReqOperationType>) -> Self {
+        self.operation_types = types;
+        self
+    }
+
+    #[must_use]
+    pub fn label<T: Into<String>>(mut self, label: T) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Only operations made at or after this time.
+    #[must_use]
+    pub const fn from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Only operations made before this time.
+    #[must_use]
+    pub const fn till(mut self, till: DateTime<Utc>) -> Self {
+        self.till = Some(till);
+        self
+    }
+
+    #[must_use]
+    pub const fn start_record(mut self, start_record: u64) -> Self {
+        self.start_record = start_record;
+        self
+    }
+
+    /// Requests operation details along with every record.
+    #[must_use]
+    pub const fn details(mut self, details: bool) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// Filters history by operation types, skipping those that cannot be used as a filter.
+    #[must_use]
+    pub fn for_types(types: &[OperationType]) -> Self {
+        Self {
+            operation_types: types
+                .iter()
+                .filter_map(|&t| ReqOperationType::try_from(t).ok())
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Latest `n` operations, fetched in pages of at most 100 records.
+    #[must_use]
+    pub fn most_recent(n: usize) -> Self {
+        Self {
+            records: Some(n.max(1).min(100) as u64),
+            ..Self::default()
+        }
+        .record_limit(n)
+    }
+
+    /// Checks that parameters do not conflict, see `ParamConflictError`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn build(self) -> Result<Self, ParamConflictError> {
+        if self.from.is_some() && self.start_record > 0 {
+            return Err(ParamConflictError {
+                first: "from",
+                second: "start_record",
+                description: "`from` filters history by date and `start_record` is a pagination offset; \
+                    set `from` for the first page only and use `start_record` for the following ones"
+                    .into(),
+            });
+        }
+
+        Ok(self)
+    }
+
+    /// Sets number of records in a page, clamped to the range allowed by the API.
+    #[must_use]
+    pub fn records_per_page(mut self, records: u64) -> Self {
+        self.records = Some(clamp_records_per_page(records));
+        self
+    }
+
+    /// Stops fetching pages once `max` records have been yielded.
+    #[must_use]
+    pub const fn record_limit(mut self, max: usize) -> Self {
+        self.record_limit = Some(max);
+        self
+    }
+
+    /// Sets `from` to local time in `tz`.
+    #[cfg(feature = "timezone")]
+    #[must_use]
+    pub fn from_local(mut self, dt: NaiveDateTime, tz: chrono_tz::Tz) -> Self {
+        self.from = Some(local_to_utc(dt, tz));
+        self
+    }
+
+    /// Sets `till` to local time in `tz`.
+    #[cfg(feature = "timezone")]
+    #[must_use]
+    pub fn till_local(mut self, dt: NaiveDateTime, tz: chrono_tz::Tz) -> Self {
+        self.till = Some(local_to_utc(dt, tz));
+        self
+    }
+
+    /// Sends parameter that has no typed support in this library yet.
+    ///
+    /// This is an escape hatch for temporary use only: once the parameter gets a typed field,
+    /// switch to it, as custom parameters are not checked in any way. Custom parameters override
+    /// typed ones with the same name.
+    #[must_use]
+    pub fn with_custom_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        if TYPED_HISTORY_PARAMS.contains(&key.as_str()) {
+            log::warn!(
+                "Custom operation history parameter {} overrides typed parameter with the same name",
+                key
+            );
+        }
+
+        self.extra_params.insert(key, value.into());
+        self
+    }
+}
+
+/// Page size range accepted by `api/operation-history`.
+pub const RECORDS_PER_PAGE_RANGE: RangeInclusive<u64> = 1..=100;
+
+pub(crate) fn clamp_records_per_page(records: u64) -> u64 {
+    let clamped = records
+        .max(*RECORDS_PER_PAGE_RANGE.start())
+        .min(*RECORDS_PER_PAGE_RANGE.end());
+    if clamped != records {
+        log::warn!(
+            "Operation history page size {} is out of range {:?}, using {}",
+            records,
+            RECORDS_PER_PAGE_RANGE,
+            clamped
+        );
+    }
+
+    clamped
+}
+
+/// Ambiguous local times resolve to the earliest instant, times skipped by DST transitions use the offset in effect after the transition.
+#[cfg(feature = "timezone")]
+fn local_to_utc(dt: NaiveDateTime, tz: chrono_tz::Tz) -> DateTime<Utc> {
+    tz.from_local_datetime(&dt).earliest().map_or_else(
+        || {
+            let offset = tz.offset_from_utc_datetime(&dt).fix();
+            DateTime::from_utc(
+                dt - chrono::Duration::seconds(offset.local_minus_utc().into()),
+                Utc,
+            )
+        },
+        |local| local.with_timezone(&Utc),
+    )
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum OperationStatus {
@@ -121,6 +780,8 @@ pub enum TransferDirection {
     Out,
 }
 
+pub type OperationId = String;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Operation {
     pub operation_id: String,
@@ -129,11 +790,159 @@ pub struct Operation {
     pub title: String,
     pub pattern_id: Option<String>,
     pub direction: TransferDirection,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub amount: BigDecimal,
     #[serde(default)]
     pub label: Option<String>,
     #[serde(rename = "type")]
-    pub operation_type: RspOperationType,
+    pub operation_type: OperationType,
+}
+
+impl Operation {
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Operation is always serializable")
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// Name of the other party. History entries only carry the title, so this is only available for shop payments;
+    /// use `OperationDetails::counterparty_name` for transfers.
+    #[must_use]
+    pub fn counterparty_name(&self) -> Option<&str> {
+        match self.operation_type {
+            OperationType::PaymentShop => Some(&self.title),
+            _ => None,
+        }
+    }
+
+    /// Always `None`: history entries do not carry sender or recipient, use `OperationDetails::counterparty_account`.
+    ///
+    /// Kept for symmetry with `OperationDetails`, so code handling both does not need to special-case history.
+    #[must_use]
+    #[allow(clippy::unused_self)]
+    pub const fn counterparty_account(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Incoming payment a merchant waits for, see `Client::reconcile`.
+#[derive(Clone, Debug)]
+pub struct ExpectedPayment {
+    pub label: String,
+    pub expected_amount: BigDecimal,
+    /// Compared to the sender account number, so only `UserId::Account` can match.
+    pub expected_from: Option<UserId>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReconciliationReport {
+    pub matched: Vec<(ExpectedPayment, Operation)>,
+    pub missing: Vec<ExpectedPayment>,
+    /// Received operations carrying expected labels that did not match any expected payment.
+    pub excess: Vec<Operation>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RawYearMonth")]
+pub struct YearMonth {
+    year: i32,
+    month: u32,
+}
+
+/// Unchecked `YearMonth`, validated by `YearMonth::new` on deserialization.
+#[derive(Deserialize)]
+struct RawYearMonth {
+    year: i32,
+    month: u32,
+}
+
+impl TryFrom<RawYearMonth> for YearMonth {
+    type Error = String;
+
+    fn try_from(RawYearMonth { year, month }: RawYearMonth) -> Result<Self, Self::Error> {
+        Self::new(year, month).ok_or_else(|| format!("invalid month {}-{}", year, month))
+    }
+}
+
+impl YearMonth {
+    /// Returns `None` if month is not in `1..=12`.
+    #[must_use]
+    pub fn new(year: i32, month: u32) -> Option<Self> {
+        if (1..=12).contains(&month) {
+            Some(Self { year, month })
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub const fn year(&self) -> i32 {
+        self.year
+    }
+
+    #[must_use]
+    pub const fn month(&self) -> u32 {
+        self.month
+    }
+
+    /// Start of the month (inclusive) and start of the next month (exclusive) in UTC.
+    ///
+    /// Returns `None` if the year is out of range supported by `chrono`.
+    #[must_use]
+    pub fn date_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let (next_year, next_month) = if self.month == 12 {
+            (self.year.checked_add(1)?, 1)
+        } else {
+            (self.year, self.month + 1)
+        };
+
+        Some((
+            Utc.ymd_opt(self.year, self.month, 1)
+                .single()?
+                .and_hms(0, 0, 0),
+            Utc.ymd_opt(next_year, next_month, 1)
+                .single()?
+                .and_hms(0, 0, 0),
+        ))
+    }
+}
+
+impl Display for YearMonth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TransactionSummary {
+    pub total_credits: BigDecimal,
+    pub total_debits: BigDecimal,
+    pub operation_count: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountStatement {
+    pub period: YearMonth,
+    /// Successful operations of the period, oldest first.
+    pub operations: Vec<OperationDetails>,
+    pub summary: TransactionSummary,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MonthlyStatement {
+    pub year: i32,
+    pub month: u32,
+    /// Approximated as `closing_balance - total_credits + total_debits`.
+    pub opening_balance: BigDecimal,
+    pub closing_balance: BigDecimal,
+    pub total_credits: BigDecimal,
+    pub total_debits: BigDecimal,
+    /// Operations of the month, oldest first.
+    pub operations: Vec<Operation>,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -144,13 +953,48 @@ pub enum RecipientType {
     Email,
 }
 
-#[derive(Clone, Debug)]
+/// Recipient of a transfer.
+///
+/// `PartialEq` is structural: the same user identified by account number and by phone number is not equal,
+/// see `semantic_eq`.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum UserId {
     Account(u64),
     Phone(phonenumber::PhoneNumber),
     Email(String),
 }
 
+impl UserId {
+    /// Whether both IDs have the same variant and value, same as `==`.
+    #[must_use]
+    pub fn structural_eq(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    /// Whether both IDs refer to the same user, comparing emails case-insensitively.
+    ///
+    /// Yandex.Money has no endpoint resolving phone numbers or emails to accounts yet, so comparing
+    /// different variants fails with `Error::InvalidParams` instead of guessing.
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn semantic_eq(
+        &self,
+        other: &Self,
+        _client: &crate::Client,
+    ) -> crate::YMResult<bool> {
+        match (self, other) {
+            (Self::Account(a), Self::Account(b)) => Ok(a == b),
+            (Self::Phone(a), Self::Phone(b)) => Ok(a == b),
+            (Self::Email(a), Self::Email(b)) => Ok(a.eq_ignore_ascii_case(b)),
+            _ => Err(crate::Error::InvalidParams {
+                description: format!(
+                    "cannot resolve whether {} and {} are the same user",
+                    self, other
+                ),
+            }),
+        }
+    }
+}
+
 impl Display for UserId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -161,23 +1005,64 @@ impl Display for UserId {
     }
 }
 
+/// Russian mobile network operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+pub enum MobileOperator {
+    #[strum(serialize = "MTS")]
+    Mts,
+    #[strum(serialize = "MegaFon")]
+    MegaFon,
+    #[strum(serialize = "Beeline")]
+    Beeline,
+    #[strum(serialize = "Tele2")]
+    Tele2,
+}
+
+impl MobileOperator {
+    /// Guesses operator from the DEF code of a Russian mobile number.
+    ///
+    /// Numbers may be ported between operators while keeping the prefix, so the result is only a hint.
+    #[must_use]
+    pub fn from_phone(phone: &phonenumber::PhoneNumber) -> Option<Self> {
+        if phone.code().value() != 7 {
+            return None;
+        }
+
+        let national = phone.national().value();
+        if !(9_000_000_000..10_000_000_000).contains(&national) {
+            return None;
+        }
+
+        match national / 10_000_000 {
+            910..=919 | 980..=989 => Some(Self::Mts),
+            920..=939 => Some(Self::MegaFon),
+            903 | 905 | 906 | 909 | 960..=969 => Some(Self::Beeline),
+            900..=902 | 904 | 908 | 950..=953 | 958 | 977 | 991..=999 => Some(Self::Tele2),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum RequestAmount {
     Total(BigDecimal),
     Net(BigDecimal),
 }
 
+/// Serde impls are derived as inherent functions and wrapped below to fill `phone_number`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct OperationDetails {
     pub operation_id: String,
     pub status: OperationStatus,
     #[serde(default)]
     pub pattern_id: Option<String>,
     pub direction: TransferDirection,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub amount: BigDecimal,
-    #[serde(default)]
+    #[serde(default, with = "crate::bigdecimal_flexible::option")]
     pub amount_due: Option<BigDecimal>,
-    #[serde(default)]
+    #[serde(default, with = "crate::bigdecimal_flexible::option")]
     pub fee: Option<BigDecimal>,
     pub datetime: DateTime<Utc>,
     pub title: String,
@@ -203,9 +1088,145 @@ pub struct OperationDetails {
     pub label: Option<String>,
     #[serde(default)]
     pub details: Option<String>,
-    pub operation_type: RspOperationType,
+    pub operation_type: OperationType,
     #[serde(default)]
     pub digital_goods: Option<String>,
+    /// Pattern-specific payment parameters, like `phone-number` of mobile payments.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub payment_parameters: HashMap<String, Value>,
+    /// Topped-up number of mobile payments, taken from `payment_parameters.phone-number`.
+    /// `None` if absent or unparseable.
+    #[serde(skip)]
+    pub phone_number: Option<phonenumber::PhoneNumber>,
+}
+
+impl Serialize for OperationDetails {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Self::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OperationDetails {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut details = Self::deserialize(deserializer)?;
+        details.phone_number = details
+            .payment_parameter("phone-number")
+            .and_then(parse_payment_phone_number);
+
+        Ok(details)
+    }
+}
+
+fn parse_payment_phone_number(number: &str) -> Option<phonenumber::PhoneNumber> {
+    // Numbers are sent without leading plus, e.g. 79001234567
+    let number = if number.starts_with('+') {
+        number.to_string()
+    } else {
+        format!("+{}", number)
+    };
+
+    phonenumber::parse(None, number).ok()
+}
+
+impl OperationDetails {
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("OperationDetails is always serializable")
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    fn payment_parameter(&self, key: &str) -> Option<&str> {
+        self.payment_parameters.get(key).and_then(Value::as_str)
+    }
+
+    #[must_use]
+    pub fn is_mobile_topup(&self) -> bool {
+        self.pattern_id.as_deref() == Some("phone-topup") && self.phone_number.is_some()
+    }
+
+    /// Website of the shop, `None` if not provided or not a valid URL.
+    #[must_use]
+    pub fn merchant_site_url(&self) -> Option<Url> {
+        ["merchant_site", "merchant_site_url"]
+            .iter()
+            .find_map(|key| self.payment_parameter(key))
+            .and_then(|site| Url::parse(site).ok())
+    }
+
+    /// Name of the shop, falling back to operation title for shop payments.
+    #[must_use]
+    pub fn merchant_name(&self) -> Option<&str> {
+        self.payment_parameter("merchant_site_name")
+            .or_else(|| match self.operation_type {
+                OperationType::PaymentShop => Some(self.title.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Operation title of shop payments, which holds the shop name.
+    #[must_use]
+    pub fn shop_title(&self) -> Option<&str> {
+        match self.operation_type {
+            OperationType::PaymentShop if !self.title.is_empty() => Some(self.title.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Sender's comment, falling back to the message for the recipient.
+    #[must_use]
+    pub fn comment_or_message(&self) -> Option<&str> {
+        self.comment.as_deref().or_else(|| self.message.as_deref())
+    }
+
+    #[must_use]
+    pub fn counterparty_name(&self) -> Option<&str> {
+        match self.operation_type {
+            OperationType::PaymentShop => Some(&self.title),
+            OperationType::OutgoingTransfer => self.recipient.as_deref(),
+            OperationType::IncomingTransfer | OperationType::IncomingTransferProtected => {
+                self.sender.as_deref()
+            }
+            OperationType::Deposition => None,
+        }
+    }
+
+    /// Account number, phone or email of the other party in a transfer.
+    #[must_use]
+    pub fn counterparty_account(&self) -> Option<String> {
+        match self.operation_type {
+            OperationType::OutgoingTransfer => self.recipient.clone(),
+            OperationType::IncomingTransfer | OperationType::IncomingTransferProtected => {
+                self.sender.clone()
+            }
+            OperationType::PaymentShop | OperationType::Deposition => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncomingTransferAcceptStatus {
+    Success,
+    Refused,
+}
+
+/// Result of accepting protected incoming transfer, see `API::incoming_transfer_accept`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IncomingTransferAcceptResponse {
+    pub status: IncomingTransferAcceptStatus,
+    /// Error code of refused accept, see `YandexErrorCode`.
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Attempts left to enter protection code, returned when the code is wrong.
+    #[serde(default)]
+    pub protection_code_attempts_available: Option<u32>,
+    /// Page where the recipient has to complete the transfer, e.g. to identify themselves.
+    #[serde(default)]
+    pub ext_action_uri: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -245,11 +1266,40 @@ pub struct MoneySources {
     pub cards: CardsSource,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Text,
+    Number,
+    Phone,
+    Email,
+    /// Field type not known to this library.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Shop pattern field to be sent as a payment parameter named `name`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RequiredField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: FieldType,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct RequestPaymentSuccessData {
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub balance: BigDecimal,
     pub request_id: String,
+    #[serde(default, with = "crate::bigdecimal_flexible::option")]
+    pub contract_amount: Option<BigDecimal>,
     pub money_source: MoneySources,
+    #[serde(rename = "test_payment", default)]
+    pub test_result: Option<bool>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -257,16 +1307,40 @@ pub struct RequestPaymentSuccessData {
 pub enum RequestPaymentResponse {
     Success(RequestPaymentSuccessData),
     HoldForPickup(RequestPaymentSuccessData),
-    Refused { error: String },
+    Refused {
+        error: String,
+        /// Fields the shop pattern needs filled in before the payment can proceed.
+        #[serde(default)]
+        required_fields: Vec<RequiredField>,
+    },
 }
 
 impl RequestPaymentResponse {
+    /// Whether the payment was requested in test mode, see `TestPaymentRequest`.
+    #[must_use]
+    pub fn is_test_payment(&self) -> bool {
+        match self {
+            Self::Success(data) | Self::HoldForPickup(data) => data.test_result.unwrap_or(false),
+            Self::Refused { .. } => false,
+        }
+    }
+
     #[allow(clippy::missing_errors_doc)]
     pub fn into_result(self) -> Result<(bool, RequestPaymentSuccessData), String> {
         match self {
             Self::Success(data) => Ok((false, data)),
             Self::HoldForPickup(data) => Ok((true, data)),
-            Self::Refused { error } => Err(error),
+            Self::Refused { error, .. } => Err(error),
+        }
+    }
+
+    #[must_use]
+    pub fn required_fields(&self) -> &[RequiredField] {
+        match self {
+            Self::Refused {
+                required_fields, ..
+            } => required_fields,
+            _ => &[],
         }
     }
 }
@@ -277,25 +1351,158 @@ pub struct Secure3DData {
     pub ext_auth_fail_uri: String,
 }
 
+impl Secure3DData {
+    /// Creates 3-D Secure redirect data, checking that both URIs are valid HTTPS URLs.
+    ///
+    /// Plain HTTP is allowed for `localhost` and `127.0.0.1` to ease development.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn new(success_uri: &str, fail_uri: &str) -> Result<Self, InvalidUri> {
+        let data = Self {
+            ext_auth_success_uri: success_uri.into(),
+            ext_auth_fail_uri: fail_uri.into(),
+        };
+        data.validate()?;
+
+        Ok(data)
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn validate(&self) -> Result<(), InvalidUri> {
+        for uri in &[&self.ext_auth_success_uri, &self.ext_auth_fail_uri] {
+            validate_callback_uri(uri)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that `uri` is an HTTPS URL, or a plain HTTP one pointing to `localhost` or `127.0.0.1`.
+pub(crate) fn validate_callback_uri(uri: &str) -> Result<Url, InvalidUri> {
+    let url = Url::parse(uri).map_err(|e| InvalidUri {
+        uri: uri.to_string(),
+        description: e.to_string(),
+    })?;
+
+    match (url.scheme(), url.host_str()) {
+        ("https", Some(_)) | ("http", Some("localhost")) | ("http", Some("127.0.0.1")) => Ok(url),
+        _ => Err(InvalidUri {
+            uri: uri.to_string(),
+            description: "must be https or http://localhost".into(),
+        }),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidUri {
+    pub uri: String,
+    pub description: String,
+}
+
+impl Display for InvalidUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid URI {}: {}", self.uri, self.description)
+    }
+}
+
+impl std::error::Error for InvalidUri {}
+
+/// Request parameters that should not be set together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamConflictError {
+    pub first: &'static str,
+    pub second: &'static str,
+    pub description: String,
+}
+
+impl Display for ParamConflictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} and {} should not be combined: {}",
+            self.first, self.second, self.description
+        )
+    }
+}
+
+impl std::error::Error for ParamConflictError {}
+
 #[derive(Clone, Debug, EnumString)]
 #[strum(serialize_all = "snake_case")]
 pub enum ProcessPaymentMoneySource {
     Wallet,
+    /// Wallet of an account that confirms outgoing payments with a transaction password.
+    #[strum(disabled)]
+    WalletWithPassword {
+        password: SecureString,
+    },
     Card {
         id: String,
         secure3d: Option<Secure3DData>,
+        /// Card security code, required for cards that demand CSC on every payment.
+        csc: Option<SecureString>,
     },
 }
 
+impl ProcessPaymentMoneySource {
+    #[must_use]
+    pub const fn from_card_id(id: String) -> Self {
+        Self::Card {
+            id,
+            secure3d: None,
+            csc: None,
+        }
+    }
+
+    #[must_use]
+    pub fn wallet_with_password<T: Into<SecureString>>(password: T) -> Self {
+        Self::WalletWithPassword {
+            password: password.into(),
+        }
+    }
+
+    /// Sets card security code. Has no effect on wallet money source.
+    #[must_use]
+    pub fn with_csc<T: Into<SecureString>>(mut self, value: T) -> Self {
+        if let Self::Card { csc, .. } = &mut self {
+            *csc = Some(value.into());
+        }
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ShopPaymentParams {
+    pub params: HashMap<String, String>,
+    pub money_source: ProcessPaymentMoneySource,
+}
+
+#[derive(Clone, Debug)]
+pub struct ValidationResult {
+    pub is_valid: bool,
+    pub contract_amount: Option<BigDecimal>,
+    pub validation_errors: Vec<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProcessPaymentSuccessData {
     pub payment_id: String,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub balance: BigDecimal,
-    pub invoice_id: String,
+    #[serde(default)]
+    pub invoice_id: Option<String>,
+    /// Debited account, may be empty for wallet payments.
+    #[serde(default)]
     pub payer: String,
     pub payee: String,
+    #[serde(with = "crate::bigdecimal_flexible")]
     pub credit_amount: BigDecimal,
     pub hold_for_pickup_link: String,
+    /// Number of the fiscal receipt issued for the payment under 54-FZ.
+    #[serde(rename = "payment_receipt_number", default)]
+    pub receipt_number: Option<String>,
+    /// Code the recipient needs to accept protected transfer.
+    #[serde(default)]
+    pub protection_code: Option<String>,
     #[serde(default)]
     pub acs_uri: Option<String>,
     #[serde(default)]
@@ -308,10 +1515,103 @@ pub struct ProcessPaymentSuccessData {
 #[serde(tag = "status", rename_all = "snake_case")]
 pub enum ProcessPaymentResponse {
     Success(ProcessPaymentSuccessData),
-    Refused { error: String },
-    InProgress { next_retry: u64 },
-    ExtAuthRequired,
-    AccountBlocked { account_unblock_uri: String },
+    Refused {
+        error: String,
+    },
+    InProgress {
+        next_retry: u64,
+    },
+    ExtAuthRequired {
+        #[serde(default)]
+        acs_uri: Option<String>,
+        #[serde(default)]
+        acs_params: Option<Value>,
+    },
+    AccountBlocked {
+        account_unblock_uri: String,
+    },
+}
+
+impl ProcessPaymentResponse {
+    /// Fiscal receipt number of the successful payment, if the API issued one.
+    #[must_use]
+    pub fn receipt_number(&self) -> Option<&str> {
+        match self {
+            Self::Success(data) => data.receipt_number.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Account debited by the successful payment, if reported by the API, see `Client::last_payer_account`.
+    #[must_use]
+    pub fn payer_account(&self) -> Option<String> {
+        match self {
+            Self::Success(data) if !data.payer.is_empty() => Some(data.payer.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Single transfer of `API::send_batch_transfers`, fields mirror `API::request_transfer` arguments.
+#[derive(Clone, Debug)]
+pub struct TransferSpec {
+    pub to: UserId,
+    pub amount: RequestAmount,
+    pub comment: String,
+    pub message: String,
+    pub label: Option<String>,
+    pub codepro: bool,
+    pub hold_for_pickup: bool,
+    pub expire_period: u32,
+}
+
+#[derive(Debug)]
+pub struct BatchTransferResult {
+    pub spec: TransferSpec,
+    pub result: crate::YMResult<ProcessPaymentResponse>,
+}
+
+/// Summary of `API::send_batch_transfers` results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    pub sent: usize,
+    pub failed: usize,
+    /// Transfers that are still being processed or wait for 3-D Secure authentication.
+    pub in_progress: usize,
+}
+
+impl BatchReport {
+    #[must_use]
+    pub fn new(results: &[BatchTransferResult]) -> Self {
+        let mut report = Self::default();
+        for res in results {
+            match &res.result {
+                Ok(ProcessPaymentResponse::Success(_)) => report.sent += 1,
+                Ok(ProcessPaymentResponse::InProgress { .. })
+                | Ok(ProcessPaymentResponse::ExtAuthRequired { .. }) => report.in_progress += 1,
+                Ok(ProcessPaymentResponse::Refused { .. })
+                | Ok(ProcessPaymentResponse::AccountBlocked { .. })
+                | Err(_) => report.failed += 1,
+            }
+        }
+
+        report
+    }
+}
+
+/// What to do after `process_payment` call, see `ProcessPaymentResponse::next_action`.
+#[derive(Clone, Debug)]
+pub enum NextAction {
+    /// Payment is done, contains payment ID.
+    Complete(String),
+    /// Payer should be redirected to the card issuer page to pass 3-D Secure check.
+    Redirect3DS {
+        url: Url,
+        params: HashMap<String, String>,
+    },
+    /// Payment is being processed, `process_payment` should be called again.
+    Pending,
+    Failed(YandexErrorCode),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -323,13 +1623,58 @@ pub enum ProcessPaymentError {
 }
 
 impl ProcessPaymentResponse {
+    #[must_use]
+    pub fn invoice_id(&self) -> Option<&str> {
+        match self {
+            Self::Success(data) => data.invoice_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_invoice_payment(&self) -> bool {
+        self.invoice_id().is_some()
+    }
+
+    #[must_use]
+    pub fn next_action(&self) -> NextAction {
+        match self {
+            Self::Success(data) => NextAction::Complete(data.payment_id.clone()),
+            Self::InProgress { .. } => NextAction::Pending,
+            Self::ExtAuthRequired {
+                acs_uri,
+                acs_params,
+            } => match acs_uri.as_deref().map(Url::parse) {
+                Some(Ok(url)) => NextAction::Redirect3DS {
+                    url,
+                    params: acs_params
+                        .as_ref()
+                        .and_then(Value::as_object)
+                        .map(|params| {
+                            params
+                                .iter()
+                                .map(|(k, v)| {
+                                    let v = v.as_str().map_or_else(|| v.to_string(), String::from);
+                                    (k.clone(), v)
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                },
+                _ => NextAction::Failed(YandexErrorCode::ExtActionRequired),
+            },
+            Self::Refused { error } => NextAction::Failed(YandexErrorCode::from(error.as_str())),
+            Self::AccountBlocked { .. } => NextAction::Failed(YandexErrorCode::AccountBlocked),
+        }
+    }
+
     #[allow(clippy::missing_errors_doc)]
     pub fn into_result(self) -> Result<ProcessPaymentSuccessData, ProcessPaymentError> {
         Err(match self {
             Self::Success(data) => return Ok(data),
             Self::Refused { error } => ProcessPaymentError::Refused { error },
             Self::InProgress { next_retry } => ProcessPaymentError::InProgress { next_retry },
-            Self::ExtAuthRequired => ProcessPaymentError::ExtAuthRequired,
+            Self::ExtAuthRequired { .. } => ProcessPaymentError::ExtAuthRequired,
             Self::AccountBlocked {
                 account_unblock_uri,
             } => ProcessPaymentError::AccountBlocked {
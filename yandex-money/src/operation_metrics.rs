@@ -0,0 +1,180 @@
+use crate::transport::{RateLimitState, SecureString, StdError, Transport};
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::Stream;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Counters of a single endpoint, see `OperationMetrics`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EndpointMetrics {
+    pub call_count: u64,
+    /// Calls that failed in transport, including non-success HTTP statuses. API errors are not counted.
+    pub error_count: u64,
+    pub total_latency_ms: u64,
+    pub max_latency_ms: u64,
+}
+
+impl EndpointMetrics {
+    #[must_use]
+    pub fn average_latency_ms(&self) -> u64 {
+        self.total_latency_ms
+            .checked_div(self.call_count)
+            .unwrap_or(0)
+    }
+
+    /// Share of failed calls, from 0 to 1.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn error_rate(&self) -> f64 {
+        if self.call_count == 0 {
+            return 0.0;
+        }
+
+        self.error_count as f64 / self.call_count as f64
+    }
+}
+
+/// Per-endpoint call statistics collected by `MetricsTransport`, see `ClientBuilder::track_operation_metrics`.
+#[derive(Debug, Default)]
+pub struct OperationMetrics {
+    endpoints: DashMap<String, EndpointMetrics>,
+}
+
+impl OperationMetrics {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, endpoint: &str, latency: Duration, is_error: bool) {
+        let latency_ms = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+
+        let mut entry = self.endpoints.entry(endpoint.to_string()).or_default();
+        entry.call_count += 1;
+        if is_error {
+            entry.error_count += 1;
+        }
+        entry.total_latency_ms = entry.total_latency_ms.saturating_add(latency_ms);
+        entry.max_latency_ms = entry.max_latency_ms.max(latency_ms);
+    }
+
+    #[must_use]
+    pub fn endpoint(&self, endpoint: &str) -> Option<EndpointMetrics> {
+        self.endpoints.get(endpoint).map(|entry| *entry)
+    }
+
+    #[must_use]
+    pub fn snapshot(&self) -> HashMap<String, EndpointMetrics> {
+        self.endpoints
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect()
+    }
+
+    /// Summary with a line per endpoint, sorted by endpoint name.
+    #[must_use]
+    pub fn report(&self) -> String {
+        let mut endpoints = self.snapshot().into_iter().collect::<Vec<_>>();
+        endpoints.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        endpoints
+            .into_iter()
+            .map(|(endpoint, m)| {
+                format!(
+                    "{}: {} calls, {} errors ({:.1}%), avg {} ms, max {} ms",
+                    endpoint,
+                    m.call_count,
+                    m.error_count,
+                    m.error_rate() * 100.0,
+                    m.average_latency_ms(),
+                    m.max_latency_ms
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Transport that records latency and outcome of every call of the inner one in `OperationMetrics`.
+#[derive(Debug)]
+pub struct MetricsTransport {
+    pub inner: Arc<dyn Transport>,
+    pub metrics: Arc<OperationMetrics>,
+}
+
+impl MetricsTransport {
+    #[must_use]
+    pub fn new(inner: Arc<dyn Transport>, metrics: Arc<OperationMetrics>) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+impl Transport for MetricsTransport {
+    fn call(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        let c = self.inner.call(endpoint, params);
+        let metrics = self.metrics.clone();
+
+        Box::pin(async move {
+            let started = Instant::now();
+            let res = c.await;
+            metrics.record(endpoint, started.elapsed(), res.is_err());
+
+            res
+        })
+    }
+
+    fn stream_response(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, StdError>> + Send + 'static>> {
+        self.inner.stream_response(endpoint, params)
+    }
+
+    fn upload_file(
+        &self,
+        endpoint: &'static str,
+        fields: &[(&str, &str)],
+        file_field: &str,
+        filename: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        self.inner
+            .upload_file(endpoint, fields, file_field, filename, content_type, data)
+    }
+
+    fn get_redirect(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        self.inner.get_redirect(endpoint, params)
+    }
+
+    fn with_token(&self, token: SecureString) -> Option<Arc<dyn Transport>> {
+        Some(Arc::new(Self {
+            inner: self.inner.with_token(token)?,
+            metrics: self.metrics.clone(),
+        }))
+    }
+
+    fn last_response_headers(&self) -> Option<HashMap<String, String>> {
+        self.inner.last_response_headers()
+    }
+
+    fn rate_limit_state(&self) -> Option<RateLimitState> {
+        self.inner.rate_limit_state()
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{models::Operation, transport::StdError, CheckpointError, YMResult};
+use async_stream::stream;
+use snafu::*;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug},
+    path::{Path, PathBuf},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::AsyncWriteExt,
+    runtime::Handle,
+    stream::{Stream, StreamExt},
+};
+
+/// Default number of records in operation history page.
+pub const DEFAULT_PAGE_SIZE: usize = 30;
+
+/// Merges locally cached operations with freshly fetched ones.
+///
+/// Operations are deduplicated by `operation_id`, with `fresh` entries taking precedence since they may carry updated status.
+/// The result is sorted by `datetime`, newest first.
+#[must_use]
+pub fn merge_operations(cached: Vec<Operation>, fresh: Vec<Operation>) -> Vec<Operation> {
+    let mut merged = cached
+        .into_iter()
+        .chain(fresh)
+        .map(|op| (op.operation_id.clone(), op))
+        .collect::<HashMap<_, _>>()
+        .into_iter()
+        .map(|(_, op)| op)
+        .collect::<Vec<_>>();
+
+    sort_by_time(&mut merged);
+
+    merged
+}
+
+fn sort_by_time(ops: &mut [Operation]) {
+    ops.sort_by(|a, b| b.datetime.cmp(&a.datetime));
+}
+
+/// Stream of operations returned by `API::operation_history`.
+pub struct OperationHistoryStream {
+    inner: Pin<Box<dyn Stream<Item = YMResult<Operation>> + Send>>,
+    page_size: usize,
+}
+
+impl Debug for OperationHistoryStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OperationHistoryStream")
+            .field("page_size", &self.page_size)
+            .finish()
+    }
+}
+
+impl OperationHistoryStream {
+    pub fn new<S>(inner: S, page_size: usize) -> Self
+    where
+        S: Stream<Item = YMResult<Operation>> + Send + 'static,
+    {
+        Self {
+            inner: Box::pin(inner),
+            page_size,
+        }
+    }
+
+    /// Returns the first operation matching `predicate`, without fetching further pages.
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn find<F>(mut self, predicate: F) -> YMResult<Option<Operation>>
+    where
+        F: Fn(&Operation) -> bool,
+    {
+        while let Some(op) = self.next().await.transpose()? {
+            if predicate(&op) {
+                return Ok(Some(op));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Yields operations whose ID starts with `prefix`.
+    ///
+    /// Assumes operations with the prefix are close to each other in history, and stops after the first page without matches.
+    #[must_use]
+    pub fn filter_by_id_prefix(self, prefix: String) -> Self {
+        let page_size = self.page_size.max(1);
+        let mut inner = self.inner;
+
+        Self::new(
+            stream! {
+                let mut seen = 0;
+                let mut matched = false;
+                while let Some(res) = inner.next().await {
+                    match res {
+                        Ok(op) => {
+                            seen += 1;
+                            if op.operation_id.starts_with(&prefix) {
+                                matched = true;
+                                yield Ok(op);
+                            }
+
+                            if seen == page_size {
+                                if !matched {
+                                    break;
+                                }
+                                seen = 0;
+                                matched = false;
+                            }
+                        }
+                        Err(e) => {
+                            yield Err(e);
+                        }
+                    }
+                }
+            },
+            page_size,
+        )
+    }
+
+    /// Synchronous iterator over the stream, polled on the runtime behind `handle`.
+    ///
+    /// The runtime must be threaded: nothing drives I/O of a basic scheduler while the iterator blocks.
+    #[must_use]
+    pub fn into_blocking_iter(self, handle: Handle) -> OperationHistoryIter {
+        OperationHistoryIter {
+            inner: self,
+            handle,
+        }
+    }
+
+    /// Buffers a page worth of operations and yields them sorted by time, newest first.
+    #[must_use]
+    pub fn sorted_by_time(self) -> SortedOperationStream {
+        let page_size = self.page_size.max(1);
+        let mut inner = self.inner;
+
+        SortedOperationStream {
+            inner: Box::pin(stream! {
+                let mut buf = Vec::with_capacity(page_size);
+                loop {
+                    let (err, done) = match inner.next().await {
+                        Some(Ok(op)) => {
+                            buf.push(op);
+                            if buf.len() < page_size {
+                                continue;
+                            }
+                            (None, false)
+                        }
+                        Some(Err(e)) => (Some(e), false),
+                        None => (None, true),
+                    };
+
+                    sort_by_time(&mut buf);
+                    for op in buf.drain(..) {
+                        yield Ok(op);
+                    }
+
+                    if let Some(e) = err {
+                        yield Err(e);
+                    }
+
+                    if done {
+                        break;
+                    }
+                }
+            }),
+        }
+    }
+
+    /// Yields runs of consecutive operations with the same key, e.g. day or counterparty.
+    ///
+    /// A group is yielded once an operation with another key arrives or the stream ends, so keys
+    /// should follow history order (newest first) for every key to appear in a single group.
+    #[must_use]
+    pub fn group_by<K, F>(self, key_fn: F) -> GroupedStream<K>
+    where
+        K: Eq + Send + 'static,
+        F: Fn(&Operation) -> K + Send + 'static,
+    {
+        let mut inner = self.inner;
+
+        GroupedStream {
+            inner: Box::pin(stream! {
+                let mut group: Option<(K, Vec<Operation>)> = None;
+                while let Some(res) = inner.next().await {
+                    let op = match res {
+                        Ok(op) => op,
+                        Err(e) => {
+                            yield Err(e);
+                            continue;
+                        }
+                    };
+
+                    let key = key_fn(&op);
+                    match &mut group {
+                        Some((current, ops)) if *current == key => ops.push(op),
+                        _ => {
+                            if let Some(complete) = group.replace((key, vec![op])) {
+                                yield Ok(complete);
+                            }
+                        }
+                    }
+                }
+
+                if let Some(complete) = group {
+                    yield Ok(complete);
+                }
+            }),
+        }
+    }
+}
+
+impl Stream for OperationHistoryStream {
+    type Item = YMResult<Operation>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Operation history stream with operations sorted by time, see `OperationHistoryStream::sorted_by_time`.
+pub struct SortedOperationStream {
+    inner: Pin<Box<dyn Stream<Item = YMResult<Operation>> + Send>>,
+}
+
+impl Debug for SortedOperationStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortedOperationStream").finish()
+    }
+}
+
+impl Stream for SortedOperationStream {
+    type Item = YMResult<Operation>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Blocking iterator over operation history, see `OperationHistoryStream::into_blocking_iter`.
+pub struct OperationHistoryIter {
+    inner: OperationHistoryStream,
+    handle: Handle,
+}
+
+impl Debug for OperationHistoryIter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OperationHistoryIter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl Iterator for OperationHistoryIter {
+    type Item = YMResult<Operation>;
+
+    /// # Panics
+    /// Panics if called from within a runtime, where blocking could deadlock.
+    fn next(&mut self) -> Option<Self::Item> {
+        assert!(
+            Handle::try_current().is_err(),
+            "OperationHistoryIter must not be used from async context"
+        );
+
+        let inner = &mut self.inner;
+        self.handle
+            .enter(|| futures::executor::block_on(inner.next()))
+    }
+}
+
+/// Operations grouped by key, see `OperationHistoryStream::group_by`.
+pub struct GroupedStream<K> {
+    inner: Pin<Box<dyn Stream<Item = YMResult<(K, Vec<Operation>)>> + Send>>,
+}
+
+impl<K> Debug for GroupedStream<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupedStream").finish()
+    }
+}
+
+impl<K> Stream for GroupedStream<K> {
+    type Item = YMResult<(K, Vec<Operation>)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Operation history stream that remembers processed operations in a file and skips them after restart.
+///
+/// ID of an operation is written to the checkpoint file when the next item is requested,
+/// so an operation interrupted by a crash is yielded again: processing should be idempotent.
+pub struct CheckpointedOperationStream {
+    inner: Pin<Box<dyn Stream<Item = YMResult<Operation>> + Send>>,
+    checkpoint_path: PathBuf,
+}
+
+impl Debug for CheckpointedOperationStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckpointedOperationStream")
+            .field("checkpoint_path", &self.checkpoint_path)
+            .finish()
+    }
+}
+
+impl CheckpointedOperationStream {
+    /// Wraps `inner`, checkpoint file is read when the stream is first polled.
+    #[must_use]
+    pub fn new(inner: OperationHistoryStream, checkpoint_path: PathBuf) -> Self {
+        let path = checkpoint_path.clone();
+        let mut inner = inner.inner;
+
+        Self {
+            inner: Box::pin(stream! {
+                let mut seen = match load_checkpoint(&path).await {
+                    Ok(seen) => seen,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+
+                while let Some(res) = inner.next().await {
+                    let op = match res {
+                        Ok(op) => op,
+                        Err(e) => {
+                            yield Err(e);
+                            continue;
+                        }
+                    };
+
+                    if seen.contains(&op.operation_id) {
+                        continue;
+                    }
+
+                    let operation_id = op.operation_id.clone();
+                    yield Ok(op);
+
+                    if let Err(e) = append_checkpoint(&path, &operation_id).await {
+                        yield Err(e);
+                        return;
+                    }
+                    seen.insert(operation_id);
+                }
+            }),
+            checkpoint_path,
+        }
+    }
+
+    /// Forgets all processed operations by removing the checkpoint file.
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn clear_checkpoint(checkpoint_path: &Path) -> YMResult<()> {
+        match tokio::fs::remove_file(checkpoint_path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(StdError::from(e)).context(CheckpointError)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Stream for CheckpointedOperationStream {
+    type Item = YMResult<Operation>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+async fn load_checkpoint(path: &Path) -> YMResult<HashSet<String>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(data) => Ok(data
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(ToString::to_string)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(StdError::from(e)).context(CheckpointError),
+    }
+}
+
+async fn append_checkpoint(path: &Path, operation_id: &str) -> YMResult<()> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(StdError::from)
+        .context(CheckpointError)?;
+
+    file.write_all(format!("{}\n", operation_id).as_bytes())
+        .await
+        .map_err(StdError::from)
+        .context(CheckpointError)?;
+    file.flush()
+        .await
+        .map_err(StdError::from)
+        .context(CheckpointError)
+}
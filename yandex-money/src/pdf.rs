@@ -0,0 +1,90 @@
+use crate::models::*;
+use printpdf::*;
+use std::io::BufWriter;
+
+const PAGE_WIDTH: f64 = 210.0;
+const PAGE_HEIGHT: f64 = 297.0;
+const MARGIN: f64 = 15.0;
+const LINE_HEIGHT: f64 = 6.0;
+const FONT_SIZE: i64 = 9;
+const TITLE_FONT_SIZE: i64 = 14;
+
+/// Column offsets from the left margin, in millimeters.
+const COLUMNS: [f64; 5] = [0.0, 30.0, 65.0, 130.0, 155.0];
+
+impl AccountStatement {
+    /// Renders statement as a PDF document with header, operation table and totals.
+    ///
+    /// Builtin PDF fonts only cover Latin characters, so Cyrillic operation titles may not display correctly.
+    #[must_use]
+    pub fn to_pdf(&self) -> Vec<u8> {
+        let title = format!("Account statement for {}", self.period);
+        let (doc, page, layer) =
+            PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Statement");
+        let font = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .expect("builtin font is always available");
+
+        let mut layer = doc.get_page(page).get_layer(layer);
+        let mut y = PAGE_HEIGHT - MARGIN;
+
+        layer.use_text(title.as_str(), TITLE_FONT_SIZE, Mm(MARGIN), Mm(y), &font);
+        y -= 2.0 * LINE_HEIGHT;
+
+        let write_row = |layer: &PdfLayerReference, y: f64, cells: &[String]| {
+            for (cell, offset) in cells.iter().zip(COLUMNS.iter()) {
+                layer.use_text(cell.as_str(), FONT_SIZE, Mm(MARGIN + offset), Mm(y), &font);
+            }
+        };
+
+        let header = ["Date", "Operation ID", "Title", "Direction", "Amount"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        write_row(&layer, y, &header);
+        y -= LINE_HEIGHT;
+
+        for op in &self.operations {
+            if y < MARGIN + 3.0 * LINE_HEIGHT {
+                let (page, new_layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Statement");
+                layer = doc.get_page(page).get_layer(new_layer);
+                y = PAGE_HEIGHT - MARGIN;
+                write_row(&layer, y, &header);
+                y -= LINE_HEIGHT;
+            }
+
+            let direction = match op.direction {
+                TransferDirection::In => "in",
+                TransferDirection::Out => "out",
+            };
+            write_row(
+                &layer,
+                y,
+                &[
+                    op.datetime.format("%Y-%m-%d %H:%M").to_string(),
+                    op.operation_id.clone(),
+                    op.title.chars().take(40).collect(),
+                    direction.to_string(),
+                    op.amount.to_string(),
+                ],
+            );
+            y -= LINE_HEIGHT;
+        }
+
+        y -= LINE_HEIGHT;
+        for line in &[
+            format!("Operations: {}", self.summary.operation_count),
+            format!("Total credits: {}", self.summary.total_credits),
+            format!("Total debits: {}", self.summary.total_debits),
+        ] {
+            layer.use_text(line.as_str(), FONT_SIZE, Mm(MARGIN), Mm(y), &font);
+            y -= LINE_HEIGHT;
+        }
+
+        let mut data = Vec::new();
+        doc.save(&mut BufWriter::new(&mut data))
+            .expect("writing to memory never fails");
+
+        data
+    }
+}
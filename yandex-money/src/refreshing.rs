@@ -0,0 +1,222 @@
+use crate::*;
+
+/// Client that refreshes rejected access tokens, see `Client::auto_refresh_token`.
+///
+/// Calls returning `YMResult` are retried once with the new token. Operation history streams and
+/// payment requests use the token that is current when they are created, and are not retried.
+pub struct RefreshingClient {
+    inner: Mutex<Arc<Client>>,
+    store: Box<dyn TokenStore>,
+    refresh_lock: tokio::sync::Mutex<()>,
+}
+
+impl Debug for RefreshingClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RefreshingClient").finish()
+    }
+}
+
+impl Client {
+    /// Refreshes access token with credentials from `store` when it is rejected, saving new tokens back to `store`.
+    ///
+    /// Refresh fails with invalid configuration error if the transport does not implement `Transport::with_token`.
+    #[must_use]
+    pub fn auto_refresh_token(self, store: Box<dyn TokenStore>) -> RefreshingClient {
+        RefreshingClient {
+            inner: Mutex::new(Arc::new(self)),
+            store,
+            refresh_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+fn is_token_rejected(e: &Error) -> bool {
+    e.is_auth_failure() || matches!(e.yandex_code(), Some(YandexErrorCode::AuthorizationReject))
+}
+
+impl RefreshingClient {
+    fn client(&self) -> Arc<Client> {
+        self.inner.lock().clone()
+    }
+
+    /// Switches to a new access token, unless another call already did so after `stale` client failed.
+    async fn refresh(&self, stale: &Arc<Client>) -> YMResult<()> {
+        let _guard = self.refresh_lock.lock().await;
+        if !Arc::ptr_eq(stale, &self.client()) {
+            return Ok(());
+        }
+
+        let credentials = self
+            .store
+            .load_refresh_credentials()
+            .await?
+            .ok_or_else(|| Error::InvalidParams {
+                description: "token store has no refresh credentials".into(),
+            })?;
+
+        let data = stale
+            .caller
+            .call::<TokenExchangeData>(
+                "oauth/token",
+                &hashmap! {
+                    "grant_type" => "refresh_token".into(),
+                    "refresh_token" => String::from(&*credentials.refresh_token),
+                    "client_id" => credentials.client_id.clone(),
+                    "client_secret" => String::from(&*credentials.client_secret),
+                },
+            )
+            .await
+            .context(TransportError)?
+            .into_result()?;
+
+        self.store.save(&data.access_token).await?;
+        if let Some(refresh_token) = data.refresh_token {
+            self.store
+                .save_refresh_credentials(&RefreshCredentials {
+                    refresh_token: refresh_token.into(),
+                    ..credentials
+                })
+                .await?;
+        }
+
+        let transport = stale
+            .caller
+            .transport
+            .with_token(SecureString::from(data.access_token))
+            .ok_or_else(|| {
+                crate::transport::Error::invalid_config(
+                    "transport does not support replacing access token",
+                )
+            })
+            .context(TransportError)?;
+        let client = Client {
+            caller: CallerWrapper::new(transport).with_retry(stale.caller.retry_policy),
+            ..Client::clone(stale)
+        };
+        *self.inner.lock() = Arc::new(client);
+
+        Ok(())
+    }
+
+    async fn with_refresh<T, F, Fut>(&self, f: F) -> YMResult<T>
+    where
+        F: Fn(Arc<Client>) -> Fut,
+        Fut: Future<Output = YMResult<T>>,
+    {
+        let client = self.client();
+        match f(client.clone()).await {
+            Err(e) if is_token_rejected(&e) => {
+                self.refresh(&client).await?;
+                f(self.client()).await
+            }
+            other => other,
+        }
+    }
+}
+
+#[async_trait]
+impl API for RefreshingClient {
+    async fn account_info(&self) -> YMResult<AccountInfo> {
+        self.with_refresh(|client| async move { client.account_info().await })
+            .await
+    }
+
+    async fn cards_list(&self) -> YMResult<CardsListResponse> {
+        self.with_refresh(|client| async move { client.cards_list().await })
+            .await
+    }
+
+    fn operation_history_with_params(
+        &self,
+        params: OperationHistoryParams,
+    ) -> OperationHistoryStream {
+        self.client().operation_history_with_params(params)
+    }
+
+    async fn operation_details(&self, operation_id: String) -> YMResult<OperationDetails> {
+        self.with_refresh(|client| {
+            let operation_id = operation_id.clone();
+            async move { client.operation_details(operation_id).await }
+        })
+        .await
+    }
+
+    async fn incoming_transfer_accept(
+        &self,
+        operation_id: String,
+        protection_code: Option<String>,
+    ) -> YMResult<IncomingTransferAcceptResponse> {
+        self.with_refresh(|client| {
+            let operation_id = operation_id.clone();
+            let protection_code = protection_code.clone();
+            async move {
+                client
+                    .incoming_transfer_accept(operation_id, protection_code)
+                    .await
+            }
+        })
+        .await
+    }
+
+    async fn incoming_transfer_reject(&self, operation_id: String) -> YMResult<()> {
+        self.with_refresh(|client| {
+            let operation_id = operation_id.clone();
+            async move { client.incoming_transfer_reject(operation_id).await }
+        })
+        .await
+    }
+
+    fn request_shop_payment(
+        &self,
+        pattern_id: String,
+        other: HashMap<String, String>,
+    ) -> PaymentRequest {
+        self.client().request_shop_payment(pattern_id, other)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn request_transfer(
+        &self,
+        to: UserId,
+        amount: RequestAmount,
+        comment: String,
+        message: String,
+        label: Option<String>,
+        codepro: bool,
+        hold_for_pickup: bool,
+        expire_period: u32,
+    ) -> PaymentRequest {
+        self.client().request_transfer(
+            to,
+            amount,
+            comment,
+            message,
+            label,
+            codepro,
+            hold_for_pickup,
+            expire_period,
+        )
+    }
+
+    fn request_mobile_payment(
+        &self,
+        phone_number: PhoneNumber,
+        amount: BigDecimal,
+    ) -> PaymentRequest {
+        self.client().request_mobile_payment(phone_number, amount)
+    }
+
+    async fn process_payment(
+        &self,
+        request_id: String,
+        money_source: ProcessPaymentMoneySource,
+    ) -> YMResult<ProcessPaymentResponse> {
+        // Payments rejected due to authorization are not processed, so it is safe to retry them
+        self.with_refresh(|client| {
+            let request_id = request_id.clone();
+            let money_source = money_source.clone();
+            async move { client.process_payment(request_id, money_source).await }
+        })
+        .await
+    }
+}
@@ -0,0 +1,50 @@
+use snafu::*;
+use std::collections::HashMap;
+
+pub const MAX_PARAM_KEY_LEN: usize = 64;
+pub const MAX_PARAM_VALUE_LEN: usize = 4096;
+
+#[derive(Debug, Snafu)]
+pub enum SanitizationError {
+    #[snafu(display("parameter {:?} contains control characters in its {}", key, part))]
+    ControlCharacter { key: String, part: &'static str },
+    #[snafu(display("parameter key {:?} exceeds {} characters", key, MAX_PARAM_KEY_LEN))]
+    KeyTooLong { key: String },
+    #[snafu(display(
+        "value of parameter {:?} exceeds {} characters",
+        key,
+        MAX_PARAM_VALUE_LEN
+    ))]
+    ValueTooLong { key: String },
+}
+
+/// Checks that request parameters do not contain control characters or excessively long keys and values.
+#[allow(clippy::implicit_hasher, clippy::missing_errors_doc)]
+pub fn sanitize_params(params: &HashMap<String, String>) -> Result<(), SanitizationError> {
+    for (k, v) in params {
+        ensure!(
+            !k.chars().any(|c| c.is_ascii_control()),
+            ControlCharacter {
+                key: k,
+                part: "key"
+            }
+        );
+        ensure!(
+            !v.chars().any(|c| c.is_ascii_control()),
+            ControlCharacter {
+                key: k,
+                part: "value"
+            }
+        );
+        ensure!(
+            k.chars().count() <= MAX_PARAM_KEY_LEN,
+            KeyTooLong { key: k }
+        );
+        ensure!(
+            v.chars().count() <= MAX_PARAM_VALUE_LEN,
+            ValueTooLong { key: k }
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! Helpers for testing code built on top of this library.
+
+pub mod fixtures;
+mod mock;
+
+pub use mock::*;
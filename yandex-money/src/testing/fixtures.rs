@@ -0,0 +1,138 @@
+//! Anonymized samples of Yandex.Money API responses.
+
+use crate::models::*;
+use serde::Deserialize;
+
+pub const ACCOUNT_INFO_JSON: &str = r#"{
+    "account": "4100100000000000",
+    "balance": 1000.45,
+    "currency": "643",
+    "account_status": "named",
+    "account_type": "personal",
+    "balance_details": {
+        "total": 1000.45,
+        "available": 1000.45,
+        "deposition_pending": 0,
+        "blocked": 0,
+        "debt": 0,
+        "hold": 0
+    },
+    "cards_linked": [
+        {
+            "pan_fragment": "510000******0000",
+            "type": "MasterCard"
+        }
+    ],
+    "avatar": {
+        "url": "https://avatars.yandex.net/get-yapic/0/0-0/islands-200",
+        "ts": "2020-03-15T14:27:00Z"
+    }
+}"#;
+
+pub const CARDS_LIST_JSON: &str = r#"{
+    "cards": [
+        {
+            "id": "card-385244400",
+            "pan_fragment": "510000******0000",
+            "type": "MasterCard",
+            "expire": "2024-08-31"
+        },
+        {
+            "id": "card-385244401",
+            "pan_fragment": "220000******0000",
+            "type": "Mir"
+        }
+    ]
+}"#;
+
+pub const OPERATION_HISTORY_JSON: &str = r#"{
+    "next_record": "1",
+    "operations": [
+        {
+            "operation_id": "1234567",
+            "status": "success",
+            "datetime": "2020-03-15T14:27:00Z",
+            "title": "Пополнение с карты ****0000",
+            "direction": "in",
+            "amount": 500.00,
+            "label": "order-42",
+            "type": "deposition"
+        }
+    ]
+}"#;
+
+pub const OPERATION_DETAILS_JSON: &str = r#"{
+    "operation_id": "1234567",
+    "status": "success",
+    "pattern_id": "p2p",
+    "direction": "out",
+    "amount": 50.25,
+    "amount_due": 50.00,
+    "fee": 0.25,
+    "datetime": "2020-03-15T14:27:00Z",
+    "title": "Перевод на счет 4100100000000001",
+    "recipient": "4100100000000001",
+    "recipient_type": "account",
+    "message": "Message to recipient",
+    "comment": "Transfer to 4100100000000001",
+    "codepro": false,
+    "details": "Transfer to 4100100000000001",
+    "operation_type": "outgoing-transfer"
+}"#;
+
+pub const REQUEST_PAYMENT_RESPONSE_JSON: &str = r#"{
+    "status": "success",
+    "balance": 1000.45,
+    "request_id": "313533313430323037355f6d6f636b",
+    "money_source": {
+        "wallet": {
+            "allowed": true
+        },
+        "cards": {
+            "allowed": true,
+            "csc_required": true,
+            "items": [
+                {
+                    "id": "card-385244400",
+                    "pan_fragment": "510000******0000",
+                    "type": "MasterCard"
+                }
+            ]
+        }
+    }
+}"#;
+
+pub const PROCESS_PAYMENT_RESPONSE_JSON: &str = r#"{
+    "status": "success",
+    "payment_id": "2ABCDE123456789",
+    "balance": 950.20,
+    "invoice_id": "1234567890",
+    "payer": "4100100000000000",
+    "payee": "4100100000000001",
+    "credit_amount": 50.00,
+    "hold_for_pickup_link": "",
+    "digital_goods": null
+}"#;
+
+fn parse<T>(name: &str, data: &str) -> T
+where
+    T: for<'de> Deserialize<'de>,
+{
+    serde_json::from_str(data).unwrap_or_else(|e| panic!("failed to parse {}: {}", name, e))
+}
+
+/// Deserializes every fixture into its model type, panicking on error.
+pub fn parse_all() {
+    parse::<AccountInfo>("ACCOUNT_INFO_JSON", ACCOUNT_INFO_JSON);
+    parse::<CardsListResponse>("CARDS_LIST_JSON", CARDS_LIST_JSON);
+    parse::<OperationHistoryResponse>("OPERATION_HISTORY_JSON", OPERATION_HISTORY_JSON);
+    parse::<OperationDetails>("OPERATION_DETAILS_JSON", OPERATION_DETAILS_JSON);
+    parse::<RequestPaymentResponse>(
+        "REQUEST_PAYMENT_RESPONSE_JSON",
+        REQUEST_PAYMENT_RESPONSE_JSON,
+    );
+    parse::<ProcessPaymentResponse>(
+        "PROCESS_PAYMENT_RESPONSE_JSON",
+        PROCESS_PAYMENT_RESPONSE_JSON,
+    );
+}
@@ -0,0 +1,100 @@
+//! Transport returning scripted responses, for testing code that calls the API without network access.
+
+use crate::transport::{HttpStatusError, SecureString, StdError, Transport};
+use http::StatusCode;
+use parking_lot::Mutex;
+use std::{collections::HashMap, collections::VecDeque, future::Future, pin::Pin, sync::Arc};
+
+/// Transport that answers calls with responses queued by the test, in order.
+///
+/// Every call takes the next expected entry and panics if its endpoint differs or the queue is empty.
+/// Use with `Client::from_transport`. Transports returned by `with_token` share the queue.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    expected: Arc<Mutex<VecDeque<(String, Result<String, StdError>)>>>,
+}
+
+impl MockTransport {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues successful response with `response_json` body for a call to `endpoint`, e.g. `api/account-info`.
+    pub fn expect_call(&self, endpoint: &str, response_json: &str) -> &Self {
+        self.expected
+            .lock()
+            .push_back((endpoint.to_string(), Ok(response_json.to_string())));
+        self
+    }
+
+    /// Queues transport failure for a call to `endpoint`.
+    pub fn expect_error(&self, endpoint: &str, error: &str) -> &Self {
+        self.expected
+            .lock()
+            .push_back((endpoint.to_string(), Err(error.into())));
+        self
+    }
+
+    /// Queues response with error HTTP `status`, like the one returned for a rejected token.
+    pub fn expect_http_error(&self, endpoint: &str, status: StatusCode, body: &str) -> &Self {
+        self.expected.lock().push_back((
+            endpoint.to_string(),
+            Err(HttpStatusError {
+                status,
+                body: body.to_string(),
+            }
+            .into()),
+        ));
+        self
+    }
+
+    /// # Panics
+    /// Panics if some of the expected calls were not made.
+    pub fn verify_all_consumed(&self) {
+        let expected = self.expected.lock();
+        assert!(
+            expected.is_empty(),
+            "expected calls were not made: {:?}",
+            expected
+                .iter()
+                .map(|(endpoint, _)| endpoint.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    fn next_response(&self, endpoint: &str) -> Result<String, StdError> {
+        let (expected, response) = self
+            .expected
+            .lock()
+            .pop_front()
+            .unwrap_or_else(|| panic!("unexpected call to {}", endpoint));
+        assert_eq!(expected, endpoint, "unexpected endpoint called");
+
+        response
+    }
+}
+
+impl Transport for MockTransport {
+    fn call(
+        &self,
+        endpoint: &'static str,
+        _params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        Box::pin(futures::future::ready(self.next_response(endpoint)))
+    }
+
+    fn get_redirect(
+        &self,
+        endpoint: &'static str,
+        _params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        Box::pin(futures::future::ready(self.next_response(endpoint)))
+    }
+
+    fn with_token(&self, _token: SecureString) -> Option<Arc<dyn Transport>> {
+        Some(Arc::new(Self {
+            expected: self.expected.clone(),
+        }))
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    transport::{SecureString, StdError},
+    TokenStoreError, YMResult,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use snafu::*;
+use std::path::PathBuf;
+
+/// Credentials needed to obtain a new access token, see `Client::auto_refresh_token`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RefreshCredentials {
+    pub client_id: String,
+    pub client_secret: SecureString,
+    pub refresh_token: SecureString,
+}
+
+/// Storage for the OAuth token obtained with `UnauthorizedClient::authorize`.
+#[async_trait]
+pub trait TokenStore: Send + Sync {
+    async fn load(&self) -> YMResult<Option<SecureString>>;
+    async fn save(&self, token: &str) -> YMResult<()>;
+    /// Removes both the token and refresh credentials.
+    async fn clear(&self) -> YMResult<()>;
+    async fn load_refresh_credentials(&self) -> YMResult<Option<RefreshCredentials>>;
+    async fn save_refresh_credentials(&self, credentials: &RefreshCredentials) -> YMResult<()>;
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct TokenFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<SecureString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh: Option<RefreshCredentials>,
+}
+
+/// Token stored in a TOML file with `token` key and optional `refresh` table.
+///
+/// Parent directories are created on save.
+#[derive(Clone, Debug)]
+pub struct FileTokenStore {
+    pub path: PathBuf,
+}
+
+impl FileTokenStore {
+    #[must_use]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    async fn read(&self) -> YMResult<TokenFile> {
+        let data = match tokio::fs::read(&self.path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(TokenFile::default()),
+            Err(e) => return Err(StdError::from(e)).context(TokenStoreError),
+        };
+
+        toml::from_slice(&data)
+            .map_err(StdError::from)
+            .context(TokenStoreError)
+    }
+
+    async fn write(&self, file: &TokenFile) -> YMResult<()> {
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(StdError::from)
+                .context(TokenStoreError)?;
+        }
+
+        let data = toml::to_vec(file)
+            .map_err(StdError::from)
+            .context(TokenStoreError)?;
+
+        tokio::fs::write(&self.path, data)
+            .await
+            .map_err(StdError::from)
+            .context(TokenStoreError)
+    }
+}
+
+#[async_trait]
+impl TokenStore for FileTokenStore {
+    async fn load(&self) -> YMResult<Option<SecureString>> {
+        Ok(self.read().await?.token)
+    }
+
+    async fn save(&self, token: &str) -> YMResult<()> {
+        let mut file = self.read().await?;
+        file.token = Some(token.into());
+
+        self.write(&file).await
+    }
+
+    async fn clear(&self) -> YMResult<()> {
+        match tokio::fs::remove_file(&self.path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(StdError::from(e)).context(TokenStoreError)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn load_refresh_credentials(&self) -> YMResult<Option<RefreshCredentials>> {
+        Ok(self.read().await?.refresh)
+    }
+
+    async fn save_refresh_credentials(&self, credentials: &RefreshCredentials) -> YMResult<()> {
+        let mut file = self.read().await?;
+        file.refresh = Some(credentials.clone());
+
+        self.write(&file).await
+    }
+}
+
+/// Token stored in an environment variable.
+///
+/// Refresh credentials are kept in variables with `_CLIENT_ID`, `_CLIENT_SECRET` and `_REFRESH_TOKEN` suffixes.
+/// `save` and `clear` only change the environment of the current process.
+#[derive(Clone, Debug)]
+pub struct EnvTokenStore {
+    pub var_name: String,
+}
+
+impl EnvTokenStore {
+    #[must_use]
+    pub fn new(var_name: String) -> Self {
+        Self { var_name }
+    }
+
+    fn refresh_var_names(&self) -> [String; 3] {
+        [
+            format!("{}_CLIENT_ID", self.var_name),
+            format!("{}_CLIENT_SECRET", self.var_name),
+            format!("{}_REFRESH_TOKEN", self.var_name),
+        ]
+    }
+}
+
+#[async_trait]
+impl TokenStore for EnvTokenStore {
+    async fn load(&self) -> YMResult<Option<SecureString>> {
+        Ok(std::env::var(&self.var_name).ok().map(SecureString::from))
+    }
+
+    async fn save(&self, token: &str) -> YMResult<()> {
+        std::env::set_var(&self.var_name, token);
+
+        Ok(())
+    }
+
+    async fn clear(&self) -> YMResult<()> {
+        std::env::remove_var(&self.var_name);
+        for name in &self.refresh_var_names() {
+            std::env::remove_var(name);
+        }
+
+        Ok(())
+    }
+
+    async fn load_refresh_credentials(&self) -> YMResult<Option<RefreshCredentials>> {
+        let [client_id, client_secret, refresh_token] = self.refresh_var_names();
+
+        Ok(
+            match (
+                std::env::var(client_id),
+                std::env::var(client_secret),
+                std::env::var(refresh_token),
+            ) {
+                (Ok(client_id), Ok(client_secret), Ok(refresh_token)) => Some(RefreshCredentials {
+                    client_id,
+                    client_secret: client_secret.into(),
+                    refresh_token: refresh_token.into(),
+                }),
+                _ => None,
+            },
+        )
+    }
+
+    async fn save_refresh_credentials(&self, credentials: &RefreshCredentials) -> YMResult<()> {
+        let [client_id, client_secret, refresh_token] = self.refresh_var_names();
+        std::env::set_var(client_id, &credentials.client_id);
+        std::env::set_var(client_secret, &*credentials.client_secret);
+        std::env::set_var(refresh_token, &*credentials.refresh_token);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::transport::StdError;
+use async_trait::async_trait;
+use std::{collections::HashMap, path::PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// Storage for payment request IDs keyed by payment label.
+#[async_trait]
+pub trait TransactionCache: Send + Sync {
+    async fn get(&self, label: &str) -> Result<Option<String>, StdError>;
+    async fn insert(&self, label: String, request_id: String) -> Result<(), StdError>;
+}
+
+/// Transaction cache stored as a JSON file on disk.
+///
+/// The file is replaced atomically on insert, through a temporary file with `.tmp` suffix next to it.
+#[derive(Clone, Debug)]
+pub struct FileTransactionCache {
+    pub path: PathBuf,
+}
+
+impl FileTransactionCache {
+    #[must_use]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    async fn load(&self) -> Result<HashMap<String, String>, StdError> {
+        match tokio::fs::read(&self.path).await {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes to a temporary file first, so that a crash mid-write leaves the previous contents intact.
+    async fn store(&self, entries: &HashMap<String, String>) -> Result<(), StdError> {
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        file.write_all(&serde_json::to_vec(entries)?).await?;
+        file.sync_all().await?;
+        drop(file);
+
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TransactionCache for FileTransactionCache {
+    async fn get(&self, label: &str) -> Result<Option<String>, StdError> {
+        Ok(self.load().await?.remove(label))
+    }
+
+    async fn insert(&self, label: String, request_id: String) -> Result<(), StdError> {
+        let mut entries = self.load().await?;
+        entries.insert(label, request_id);
+        self.store(&entries).await
+    }
+}
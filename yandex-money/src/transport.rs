@@ -1,22 +1,53 @@
-use http::StatusCode;
+use async_stream::try_stream;
+use bytes::Bytes;
+use futures::{Stream, StreamExt, TryStreamExt};
+use http::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode,
+};
 use log::*;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use snafu::*;
-use std::{collections::HashMap, fmt::Debug, future::Future, pin::Pin, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    fmt::{Debug, Display},
+    future::Future,
+    ops::Deref,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio_util::codec::{FramedRead, LinesCodec};
+use url::Url;
 
 pub type StdError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
+    #[snafu(display("Network error: {}", source))]
     NetworkError {
         source: StdError,
         backtrace: Backtrace,
     },
+    #[snafu(display("Failed to parse response: {}", source))]
     ParseError {
         source: StdError,
         backtrace: Backtrace,
     },
+    #[snafu(display("HTTP error {}: {}", status, body))]
+    HttpError {
+        status: u16,
+        body: String,
+        backtrace: Backtrace,
+    },
+    /// Client was misconfigured, repeating the request will not help.
+    #[snafu(display("Invalid client configuration: {}", description))]
+    InvalidConfig {
+        description: String,
+        backtrace: Backtrace,
+    },
 }
 
 impl Error {
@@ -33,8 +64,30 @@ impl Error {
     {
         ParseError.into_error(Box::new(error))
     }
+
+    pub fn invalid_config<T: Into<String>>(description: T) -> Self {
+        InvalidConfig {
+            description: description.into(),
+        }
+        .into_error(NoneError)
+    }
 }
 
+/// Response with non-success HTTP status, returned by `RemoteCaller` as transport error source.
+#[derive(Clone, Debug)]
+pub struct HttpStatusError {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Received error {} with data: {}", self.status, self.body)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase", untagged)]
 pub enum Rsp<T> {
@@ -42,6 +95,80 @@ pub enum Rsp<T> {
     OK(T),
 }
 
+impl<T> Rsp<T> {
+    /// Transforms successful value, keeping error as is.
+    #[must_use]
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Rsp<U> {
+        match self {
+            Self::Error { error } => Rsp::Error { error },
+            Self::OK(v) => Rsp::OK(f(v)),
+        }
+    }
+
+    /// Chains response-producing computation on successful value.
+    #[must_use]
+    pub fn and_then<U, F: FnOnce(T) -> Rsp<U>>(self, f: F) -> Rsp<U> {
+        match self {
+            Self::Error { error } => Rsp::Error { error },
+            Self::OK(v) => f(v),
+        }
+    }
+
+    #[must_use]
+    pub fn ok(self) -> Option<T> {
+        match self {
+            Self::Error { .. } => None,
+            Self::OK(v) => Some(v),
+        }
+    }
+
+    /// Error code returned by the API, see `YandexErrorCode`.
+    #[must_use]
+    pub fn err(self) -> Option<String> {
+        match self {
+            Self::Error { error } => Some(error),
+            Self::OK(_) => None,
+        }
+    }
+}
+
+/// String that is never revealed through `Display` or `Debug`, for use with secrets like bearer tokens.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecureString(String);
+
+impl Display for SecureString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<redacted>")
+    }
+}
+
+impl Debug for SecureString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecureString(<redacted>)")
+    }
+}
+
+impl Deref for SecureString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecureString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecureString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
 pub trait Transport: Debug + Send + Sync + 'static {
     fn call(
         &self,
@@ -54,46 +181,504 @@ pub trait Transport: Debug + Send + Sync + 'static {
         endpoint: &'static str,
         params: &HashMap<&str, String>,
     ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>>;
+
+    /// Response body as a stream of chunks, for endpoints that send data incrementally.
+    ///
+    /// By default the whole response of `call` is returned as a single chunk.
+    fn stream_response(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, StdError>> + Send + 'static>> {
+        let c = self.call(endpoint, params);
+
+        Box::pin(futures::stream::once(
+            async move { c.await.map(Bytes::from) },
+        ))
+    }
+
+    /// Sends form with text `fields` and a single file, as `multipart/form-data`.
+    ///
+    /// Transports that cannot upload files fail with an error by default.
+    fn upload_file(
+        &self,
+        endpoint: &'static str,
+        _fields: &[(&str, &str)],
+        _file_field: &str,
+        _filename: &str,
+        _content_type: &str,
+        _data: &[u8],
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        Box::pin(futures::future::err(StdError::from(format!(
+            "transport does not support file uploads to {}",
+            endpoint
+        ))))
+    }
+
+    /// Sends several requests at once, failing if any of them fails.
+    ///
+    /// The API has no batch endpoint, so by default requests are just made concurrently, reusing pooled connections.
+    fn batch_call(
+        &self,
+        requests: Vec<(&'static str, HashMap<&str, String>)>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>, StdError>> + Send + 'static>> {
+        let calls = requests
+            .iter()
+            .map(|(endpoint, params)| self.call(*endpoint, params))
+            .collect::<Vec<_>>();
+
+        Box::pin(async move { futures::future::join_all(calls).await.into_iter().collect() })
+    }
+
+    /// Same transport authorized with another token, or `None` if the transport cannot be reconfigured.
+    fn with_token(&self, _token: SecureString) -> Option<Arc<dyn Transport>> {
+        None
+    }
+
+    /// Headers of the most recent response, keyed by lowercase name. Transports that do not track them return `None`.
+    fn last_response_headers(&self) -> Option<HashMap<String, String>> {
+        None
+    }
+
+    /// Rate limiting observed so far, `None` for transports that do not track it.
+    fn rate_limit_state(&self) -> Option<RateLimitState> {
+        None
+    }
+}
+
+pub(crate) const DEFAULT_ADDR: &str = "https://yoomoney.ru";
+
+/// Environment variable overriding `DEFAULT_ADDR`, e.g. to point clients at a mirror.
+pub(crate) const ADDR_ENV_VAR: &str = "YANDEX_MONEY_API_URL";
+
+/// API address from `ADDR_ENV_VAR` if it is set to a valid URL, `DEFAULT_ADDR` otherwise.
+pub(crate) fn default_addr() -> Url {
+    if let Ok(value) = std::env::var(ADDR_ENV_VAR) {
+        match Url::parse(&value) {
+            Ok(url) if url.has_host() => return origin_url(url),
+            _ => warn!(
+                "Ignoring {}: {} is not a valid base URL",
+                ADDR_ENV_VAR, value
+            ),
+        }
+    }
+
+    Url::parse(DEFAULT_ADDR).expect("default address is a valid URL; qed")
+}
+
+/// Strips everything but scheme, host and port from the URL.
+pub(crate) fn origin_url(mut url: Url) -> Url {
+    url.set_path("");
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
+/// Version of the API whose endpoints are called.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    #[cfg(feature = "v2")]
+    V2,
+    /// Path prefix to use instead of `api`, e.g. `api/v3`.
+    Custom(String),
+}
+
+impl Default for ApiVersion {
+    fn default() -> Self {
+        Self::V1
+    }
+}
+
+impl ApiVersion {
+    fn prefix(&self) -> &str {
+        match self {
+            Self::V1 => "api",
+            #[cfg(feature = "v2")]
+            Self::V2 => "api/v2",
+            Self::Custom(prefix) => prefix.trim_matches('/'),
+        }
+    }
+
+    /// Rewrites `api/` endpoint for this version, leaving other endpoints (like OAuth ones) as is.
+    #[must_use]
+    pub fn endpoint(&self, endpoint: &str) -> String {
+        match endpoint.strip_prefix("api/") {
+            Some(method) => format!("{}/{}", self.prefix(), method),
+            None => endpoint.to_string(),
+        }
+    }
+}
+
+pub(crate) fn parse_root_certificate(cert_pem: &[u8]) -> Result<reqwest::Certificate, Error> {
+    reqwest::Certificate::from_pem(cert_pem)
+        .map_err(|e| Error::invalid_config(format!("failed to parse root certificate: {}", e)))
+}
+
+pub(crate) fn header_map(headers: &[(String, String)]) -> Result<HeaderMap, Error> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.append(
+            HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                Error::invalid_config(format!("invalid header name {}: {}", name, e))
+            })?,
+            HeaderValue::from_str(value).map_err(|e| {
+                Error::invalid_config(format!("invalid value of header {}: {}", name, e))
+            })?,
+        );
+    }
+
+    Ok(map)
 }
 
 #[derive(Debug)]
 pub struct RemoteCaller {
     pub http_client: reqwest::Client,
-    pub addr: String,
-    pub bearer: Option<String>,
+    /// Base URL of the API, only scheme, host and port are used.
+    pub addr: Url,
+    pub bearer: Option<SecureString>,
+    pub api_version: ApiVersion,
+    /// Headers sent with every request, should also be set as default headers of `http_client`.
+    pub extra_headers: Vec<(String, String)>,
+    /// Lowercase names of `extra_headers` whose values are not written to trace logs.
+    pub sensitive_headers: HashSet<String>,
+    /// Headers of the most recent response to `call`.
+    pub last_headers: Arc<Mutex<Option<HashMap<String, String>>>>,
+    /// Timeout of establishing connection, should also be set in `http_client`.
+    pub connect_timeout: Option<Duration>,
+    /// Timeout of the whole request, applied to every request made by the caller.
+    pub request_timeout: Option<Duration>,
+}
+
+/// Builder of `RemoteCaller` with its HTTP client, see `RemoteCaller::builder`.
+#[derive(Clone, Debug, Default)]
+pub struct RemoteCallerBuilder {
+    token: Option<SecureString>,
+    root_certificates: Vec<Vec<u8>>,
+    extra_headers: Vec<(String, String)>,
+    sensitive_headers: HashSet<String>,
+    user_agent: Option<String>,
+    api_version: ApiVersion,
+    proxy: Option<Url>,
+    base_url: Option<Url>,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+}
+
+impl RemoteCallerBuilder {
+    #[must_use]
+    pub fn token<T: Into<SecureString>>(mut self, token: T) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// See `RemoteCaller::with_custom_tls_root` for security implications.
+    #[must_use]
+    pub fn trust_certificate(mut self, cert_pem: Vec<u8>) -> Self {
+        self.root_certificates.push(cert_pem);
+        self
+    }
+
+    #[must_use]
+    pub fn add_header(mut self, name: &str, value: &str) -> Self {
+        self.extra_headers.push((name.into(), value.into()));
+        self
+    }
+
+    #[must_use]
+    pub fn sensitive_header(mut self, name: &str) -> Self {
+        self.sensitive_headers.insert(name.to_lowercase());
+        self
+    }
+
+    #[must_use]
+    pub fn user_agent<T: Into<String>>(mut self, agent: T) -> Self {
+        self.user_agent = Some(agent.into());
+        self
+    }
+
+    #[must_use]
+    pub fn proxy(mut self, url: Url) -> Self {
+        self.proxy = Some(url);
+        self
+    }
+
+    #[must_use]
+    pub fn base_url(mut self, url: Url) -> Self {
+        self.base_url = Some(url);
+        self
+    }
+
+    #[must_use]
+    pub fn api_version(mut self, version: ApiVersion) -> Self {
+        self.api_version = version;
+        self
+    }
+
+    #[must_use]
+    pub const fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub const fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Fails if a certificate, header, proxy or base URL is invalid.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn build(self) -> Result<RemoteCaller, Error> {
+        let mut http_client =
+            reqwest::Client::builder().default_headers(header_map(&self.extra_headers)?);
+        if let Some(user_agent) = &self.user_agent {
+            http_client = http_client.user_agent(user_agent);
+        }
+        for cert_pem in &self.root_certificates {
+            http_client = http_client.add_root_certificate(parse_root_certificate(cert_pem)?);
+        }
+        if let Some(url) = &self.proxy {
+            if !["http", "https", "socks5", "socks5h"].contains(&url.scheme()) {
+                return Err(Error::invalid_config(format!(
+                    "unsupported proxy scheme {}",
+                    url.scheme()
+                )));
+            }
+            http_client = http_client.proxy(
+                reqwest::Proxy::all(url.as_str())
+                    .map_err(|e| Error::invalid_config(format!("invalid proxy {}: {}", url, e)))?,
+            );
+        }
+        if let Some(timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.request_timeout {
+            http_client = http_client.timeout(timeout);
+        }
+        let http_client = http_client.build().map_err(Error::from_network_error)?;
+
+        let addr = match self.base_url {
+            Some(url) if !url.has_host() => {
+                return Err(Error::invalid_config(format!(
+                    "base URL {} has no host",
+                    url
+                )))
+            }
+            Some(url) => origin_url(url),
+            None => default_addr(),
+        };
+
+        Ok(RemoteCaller {
+            http_client,
+            addr,
+            bearer: self.token,
+            api_version: self.api_version,
+            extra_headers: self.extra_headers,
+            sensitive_headers: self.sensitive_headers,
+            last_headers: Default::default(),
+            connect_timeout: self.connect_timeout,
+            request_timeout: self.request_timeout,
+        })
+    }
+}
+
+impl RemoteCaller {
+    #[must_use]
+    pub fn builder() -> RemoteCallerBuilder {
+        RemoteCallerBuilder::default()
+    }
+
+    /// Creates caller that additionally trusts the PEM-encoded root certificate.
+    ///
+    /// **Security warning**: any server presenting a certificate issued by this root will be trusted
+    /// as Yandex.Money. Only use certificates of TLS-intercepting proxies you control.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn with_custom_tls_root(cert_pem: Vec<u8>) -> Result<Self, Error> {
+        Self::builder().trust_certificate(cert_pem).build()
+    }
+
+    fn post(&self, endpoint: &str) -> reqwest::RequestBuilder {
+        let uri = format!(
+            "{}/{}",
+            self.addr.as_str().trim_end_matches('/'),
+            self.api_version.endpoint(endpoint)
+        );
+
+        let mut req = self.http_client.post(&uri);
+        if let Some(bearer) = self.bearer.as_ref() {
+            req = req.bearer_auth(&**bearer);
+        }
+
+        req
+    }
+
+    fn headers_trace(&self) -> String {
+        self.extra_headers
+            .iter()
+            .map(|(name, value)| {
+                if self.sensitive_headers.contains(&name.to_lowercase()) {
+                    format!("{}: <redacted>", name)
+                } else {
+                    format!("{}: {}", name, value)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Request parameters whose values are not written to trace logs.
+const SENSITIVE_PARAMS: &[&str] = &["ext_auth_password", "csc", "client_secret", "refresh_token"];
+
+fn params_trace(params: &HashMap<&str, String>) -> String {
+    let params = params
+        .iter()
+        .map(|(name, value)| {
+            if SENSITIVE_PARAMS.contains(name) {
+                format!("{:?}: <redacted>", name)
+            } else {
+                format!("{:?}: {:?}", name, value)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    format!("{{{}}}", params.join(", "))
+}
+
+/// # Panics
+/// Panics if `addr` has no host, use `RemoteCaller::builder` to handle this as an error.
+impl From<Url> for RemoteCaller {
+    fn from(addr: Url) -> Self {
+        Self::builder()
+            .base_url(addr)
+            .build()
+            .unwrap_or_else(|e| panic!("failed to create caller: {}", e))
+    }
 }
 
 impl Transport for RemoteCaller {
+    // Future is only wrapped in a span with `tracing` feature
+    #[allow(clippy::let_and_return)]
     fn call(
         &self,
         endpoint: &'static str,
         params: &HashMap<&str, String>,
     ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
-        let client = self.http_client.clone();
-        let uri = format!("{}/{}", self.addr, endpoint);
-        let params_trace = format!("{:?}", params);
+        let params_trace = params_trace(params);
+        let headers_trace = self.headers_trace();
+        let last_headers = self.last_headers.clone();
+        let req = self.post(endpoint).form(params);
 
-        let mut req = client.post(&uri).form(params);
-        if let Some(bearer) = self.bearer.as_ref() {
-            req = req.bearer_auth(&bearer);
-        }
+        let fut: Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> =
+            Box::pin(async move {
+                trace!(
+                    "Sending request to endpoint {} with headers: [{}] and params: {}",
+                    endpoint,
+                    headers_trace,
+                    params_trace
+                );
+
+                let rsp = req.send().await?;
+                let status = rsp.status();
+
+                *last_headers.lock() = Some(
+                    rsp.headers()
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+                        })
+                        .collect(),
+                );
+
+                let data = rsp.text().await?;
+
+                trace!("Received HTTP response: {}", data);
+
+                if status.is_client_error() || status.is_server_error() {
+                    return Err(Box::new(HttpStatusError { status, body: data }));
+                }
+
+                Ok(data)
+            });
+
+        // Span has to wrap the returned future, as the request is only sent when it is polled
+        #[cfg(feature = "tracing")]
+        let fut = Box::pin(tracing::Instrument::instrument(
+            fut,
+            tracing::debug_span!("call", endpoint),
+        ));
+
+        fut
+    }
+
+    fn stream_response(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, StdError>> + Send + 'static>> {
+        let params_trace = params_trace(params);
+        let req = self.post(endpoint).form(params);
+
+        Box::pin(
+            futures::stream::once(async move {
+                trace!(
+                    "Sending streaming request to endpoint {} with params: {}",
+                    endpoint,
+                    params_trace
+                );
+
+                let rsp = req.send().await?;
+                let status = rsp.status();
+                if status.is_client_error() || status.is_server_error() {
+                    let body = rsp.text().await?;
+                    return Err(StdError::from(HttpStatusError { status, body }));
+                }
+
+                Ok(rsp.bytes_stream().map_err(StdError::from))
+            })
+            .try_flatten(),
+        )
+    }
+
+    fn upload_file(
+        &self,
+        endpoint: &'static str,
+        fields: &[(&str, &str)],
+        file_field: &str,
+        filename: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        let fields_trace = format!("{:?}", fields);
+        let file_trace = format!("{} ({}, {} bytes)", filename, content_type, data.len());
+        let part = reqwest::multipart::Part::bytes(data.to_vec())
+            .file_name(filename.to_string())
+            .mime_str(content_type);
+        let form = fields
+            .iter()
+            .fold(reqwest::multipart::Form::new(), |form, (name, value)| {
+                form.text(name.to_string(), value.to_string())
+            });
+        let file_field = file_field.to_string();
+        let req = self.post(endpoint);
 
         Box::pin(async move {
             trace!(
-                "Sending request to endpoint {} with params: {}",
+                "Uploading file {} to endpoint {} with fields: {}",
+                file_trace,
                 endpoint,
-                params_trace
+                fields_trace
             );
 
-            let rsp = req.send().await?;
-            let err = rsp.error_for_status_ref().err();
-
+            let rsp = req.multipart(form.part(file_field, part?)).send().await?;
+            let status = rsp.status();
             let data = rsp.text().await?;
 
             trace!("Received HTTP response: {}", data);
 
-            if let Some(err) = err {
-                return Err(format!("Received error {} with data: {}", err, data).into());
+            if status.is_client_error() || status.is_server_error() {
+                return Err(Box::new(HttpStatusError { status, body: data }));
             }
 
             Ok(data)
@@ -105,7 +690,11 @@ impl Transport for RemoteCaller {
         endpoint: &'static str,
         params: &HashMap<&str, String>,
     ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
-        let uri = format!("{}/{}", self.addr, endpoint);
+        let uri = format!(
+            "{}/{}",
+            self.addr.as_str().trim_end_matches('/'),
+            self.api_version.endpoint(endpoint)
+        );
 
         let redirect_url = Arc::new(Mutex::new(None));
         let client = reqwest::Client::builder()
@@ -119,7 +708,7 @@ impl Transport for RemoteCaller {
             .build()
             .map(|client| client.post(&uri).form(params));
 
-        let params_trace = format!("{:?}", params);
+        let params_trace = params_trace(params);
 
         Box::pin(async move {
             trace!(
@@ -139,14 +728,249 @@ impl Transport for RemoteCaller {
             }
         })
     }
+
+    fn with_token(&self, token: SecureString) -> Option<Arc<dyn Transport>> {
+        Some(Arc::new(Self {
+            http_client: self.http_client.clone(),
+            addr: self.addr.clone(),
+            bearer: Some(token),
+            api_version: self.api_version.clone(),
+            extra_headers: self.extra_headers.clone(),
+            sensitive_headers: self.sensitive_headers.clone(),
+            last_headers: Default::default(),
+            connect_timeout: self.connect_timeout,
+            request_timeout: self.request_timeout,
+        }))
+    }
+
+    fn last_response_headers(&self) -> Option<HashMap<String, String>> {
+        self.last_headers.lock().clone()
+    }
+}
+
+/// Rate limiting observed by `RateLimitedTransport`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RateLimitState {
+    /// Whether the last call was rejected with HTTP 429.
+    pub limited: bool,
+    /// When the limit is expected to be lifted, known if the server sent `Retry-After` header.
+    pub resets_at: Option<Instant>,
+}
+
+impl RateLimitState {
+    /// Whether requests should still be held back at `now`.
+    #[must_use]
+    pub fn is_active(&self, now: Instant) -> bool {
+        self.limited && self.resets_at.map_or(true, |resets_at| now < resets_at)
+    }
+}
+
+/// Parses `Retry-After` header, which is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    (chrono::DateTime::parse_from_rfc2822(value).ok()? - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+/// Transport that tracks HTTP 429 responses of the inner one, see `Client::is_rate_limited`.
+///
+/// The state is set on every 429 response and cleared by the next successful call.
+#[derive(Debug)]
+pub struct RateLimitedTransport {
+    pub inner: Arc<dyn Transport>,
+    pub state: Arc<Mutex<RateLimitState>>,
+}
+
+impl RateLimitedTransport {
+    #[must_use]
+    pub fn new(inner: Arc<dyn Transport>) -> Self {
+        Self {
+            inner,
+            state: Default::default(),
+        }
+    }
+}
+
+impl Transport for RateLimitedTransport {
+    fn call(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        let c = self.inner.call(endpoint, params);
+        let inner = self.inner.clone();
+        let state = self.state.clone();
+
+        Box::pin(async move {
+            let res = c.await;
+            match &res {
+                Ok(_) => *state.lock() = RateLimitState::default(),
+                Err(e) => {
+                    let rate_limited = e
+                        .downcast_ref::<HttpStatusError>()
+                        .map_or(false, |e| e.status == StatusCode::TOO_MANY_REQUESTS);
+                    if rate_limited {
+                        let resets_at = inner
+                            .last_response_headers()
+                            .and_then(|headers| parse_retry_after(headers.get("retry-after")?))
+                            .map(|delay| Instant::now() + delay);
+
+                        *state.lock() = RateLimitState {
+                            limited: true,
+                            resets_at,
+                        };
+                    }
+                }
+            }
+
+            res
+        })
+    }
+
+    fn stream_response(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, StdError>> + Send + 'static>> {
+        self.inner.stream_response(endpoint, params)
+    }
+
+    fn upload_file(
+        &self,
+        endpoint: &'static str,
+        fields: &[(&str, &str)],
+        file_field: &str,
+        filename: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        self.inner
+            .upload_file(endpoint, fields, file_field, filename, content_type, data)
+    }
+
+    fn get_redirect(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        self.inner.get_redirect(endpoint, params)
+    }
+
+    fn with_token(&self, token: SecureString) -> Option<Arc<dyn Transport>> {
+        Some(Arc::new(Self {
+            inner: self.inner.with_token(token)?,
+            state: self.state.clone(),
+        }))
+    }
+
+    fn last_response_headers(&self) -> Option<HashMap<String, String>> {
+        self.inner.last_response_headers()
+    }
+
+    fn rate_limit_state(&self) -> Option<RateLimitState> {
+        Some(*self.state.lock())
+    }
+}
+
+/// Delay before the first retry of `RetryPolicy::default_exponential`.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Policy of repeating transport calls that failed with network errors, see `CallerWrapper::with_retry`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    /// Factor the delay is multiplied by after each attempt.
+    pub multiplier: f64,
+    /// Maximum share of the delay randomly added or subtracted, from 0 to 1.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::no_retry()
+    }
+}
+
+impl RetryPolicy {
+    #[must_use]
+    pub const fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_delay: Duration::from_secs(0),
+            multiplier: 1.0,
+            jitter: 0.0,
+        }
+    }
+
+    /// 4 attempts with delays starting at 500 ms and doubling after each attempt, with 10% jitter.
+    #[must_use]
+    pub const fn default_exponential() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay: RETRY_BASE_DELAY,
+            multiplier: 2.0,
+            jitter: 0.1,
+        }
+    }
+
+    /// Delay after `attempt`-th failed attempt, capped at 30 seconds.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        use rand::Rng;
+
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let mut secs = (self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent))
+            .min(RETRY_MAX_DELAY.as_secs_f64());
+        let jitter = self.jitter.max(0.0).min(1.0);
+        if jitter > 0.0 {
+            secs *= 1.0 + rand::thread_rng().gen_range(-jitter, jitter);
+        }
+
+        if secs.is_finite() && secs > 0.0 {
+            Duration::from_secs_f64(secs)
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+}
+
+/// Whether the failed transport call may succeed if repeated: connection problems and server errors are retried,
+/// client errors like rejected authorization are not.
+fn is_retryable_transport_error(e: &StdError) -> bool {
+    e.downcast_ref::<HttpStatusError>()
+        .map_or(true, |e| e.status.is_server_error())
 }
 
 #[derive(Clone, Debug)]
 pub struct CallerWrapper {
     pub transport: Arc<dyn Transport>,
+    /// Policy of repeating failed `call`s, no retries by default.
+    pub retry_policy: RetryPolicy,
 }
 
 impl CallerWrapper {
+    #[must_use]
+    pub fn new(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            retry_policy: RetryPolicy::no_retry(),
+        }
+    }
+
+    /// Repeats `call`s that fail with network errors according to `policy`.
+    #[must_use]
+    pub const fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
     pub fn call<T>(
         &self,
         method: &'static str,
@@ -155,24 +979,227 @@ impl CallerWrapper {
     where
         T: for<'de> Deserialize<'de> + Send + 'static,
     {
-        let c = self.transport.call(method, params);
+        self.call_raw(method, params)
+    }
+
+    /// Same as `call`, but parses the response as `T` itself rather than `Rsp<T>`.
+    ///
+    /// For endpoints whose error responses carry data besides the error code, which `Rsp::Error` would drop.
+    #[allow(clippy::let_and_return)]
+    pub fn call_raw<T>(
+        &self,
+        method: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> impl Future<Output = Result<T, Error>> + Send + 'static
+    where
+        T: for<'de> Deserialize<'de> + Send + 'static,
+    {
+        let transport = self.transport.clone();
+        let policy = self.retry_policy;
+        let params = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let fut = async move {
+            let mut attempt = 1;
+            let data = loop {
+                let params = params
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.clone()))
+                    .collect();
+
+                match transport.call(method, &params).await {
+                    Ok(data) => break data,
+                    Err(e) if attempt < policy.max_attempts && is_retryable_transport_error(&e) => {
+                        debug!("Retrying call to {} after error: {}", method, e);
+                        tokio::time::delay_for(policy.delay(attempt)).await;
+                        attempt += 1;
+                    }
+                    Err(e) => {
+                        return match e.downcast::<HttpStatusError>() {
+                            // Error status with JSON error body is parsed like a regular response
+                            Ok(e) => {
+                                match serde_json::from_str::<Rsp<serde_json::Value>>(&e.body) {
+                                    Ok(Rsp::Error { .. }) => serde_json::from_str::<T>(&e.body)
+                                        .map_err(Error::from_parse_error),
+                                    _ => HttpError {
+                                        status: e.status.as_u16(),
+                                        body: e.body,
+                                    }
+                                    .fail(),
+                                }
+                            }
+                            Err(e) => Err(e).context(NetworkError),
+                        };
+                    }
+                }
+            };
+
+            serde_json::from_str::<T>(&data).map_err(Error::from_parse_error)
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = tracing::Instrument::instrument(fut, tracing::debug_span!("call_raw", method));
+
+        fut
+    }
+
+    /// Same as `call`, but also converts API errors and repeats the call while `should_retry` returns `true`.
+    ///
+    /// Number of attempts and delays between them follow `retry_policy`, see `with_retry`.
+    /// Unlike `call`, network errors are only repeated if `should_retry` returns `true` for them.
+    pub fn call_with_retry<T, R>(
+        &self,
+        method: &'static str,
+        params: &HashMap<&str, String>,
+        should_retry: R,
+    ) -> impl Future<Output = crate::YMResult<T>> + Send + 'static
+    where
+        T: for<'de> Deserialize<'de> + Send + 'static,
+        R: Fn(&crate::YMResult<T>) -> bool + Send + 'static,
+    {
+        // Every attempt is a single call, retries are only made by the loop below
+        let caller = Self::new(self.transport.clone());
+        let policy = self.retry_policy;
+        let params = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<HashMap<_, _>>();
+
         async move {
-            Ok(serde_json::from_str(&c.await.context(NetworkError)?)
-                .map_err(Error::from_parse_error)?)
+            let mut attempt = 1;
+            loop {
+                let params = params
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.clone()))
+                    .collect();
+
+                let res = caller
+                    .call(method, &params)
+                    .await
+                    .context(crate::TransportError)
+                    .and_then(Rsp::into_result);
+
+                if res.is_ok() || attempt >= policy.max_attempts || !should_retry(&res) {
+                    return res;
+                }
+
+                tokio::time::delay_for(policy.delay(attempt)).await;
+                attempt += 1;
+            }
         }
     }
 
+    /// Typed version of `Transport::batch_call`, responses are in the order of `requests`.
+    pub fn batch_call<T>(
+        &self,
+        requests: Vec<(&'static str, HashMap<&str, String>)>,
+    ) -> impl Future<Output = Result<Vec<Rsp<T>>, Error>> + Send + 'static
+    where
+        T: for<'de> Deserialize<'de> + Send + 'static,
+    {
+        let c = self.transport.batch_call(requests);
+        async move {
+            c.await
+                .context(NetworkError)?
+                .iter()
+                .map(|data| serde_json::from_str(data).map_err(Error::from_parse_error))
+                .collect()
+        }
+    }
+
+    /// Calls endpoint that returns no data on success, but may return JSON error.
+    ///
+    /// Empty and non-error JSON bodies are treated as success. Error statuses without JSON error body result in `Error::HttpError`.
     pub fn call_empty(
         &self,
         method: &'static str,
         params: &HashMap<&str, String>,
-    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+    ) -> impl Future<Output = Result<Rsp<()>, Error>> + Send + 'static {
         let c = self.transport.call(method, params);
 
         async move {
-            c.await.context(NetworkError)?;
+            let body = match c.await {
+                Ok(body) => body,
+                Err(e) => {
+                    return match e.downcast::<HttpStatusError>() {
+                        Ok(e) => match serde_json::from_str::<Rsp<serde_json::Value>>(&e.body) {
+                            Ok(Rsp::Error { error }) => Ok(Rsp::Error { error }),
+                            _ => HttpError {
+                                status: e.status.as_u16(),
+                                body: e.body,
+                            }
+                            .fail(),
+                        },
+                        Err(e) => Err(e).context(NetworkError),
+                    }
+                }
+            };
 
-            Ok(())
+            if body.trim().is_empty() {
+                return Ok(Rsp::OK(()));
+            }
+
+            match serde_json::from_str::<Rsp<serde_json::Value>>(&body)
+                .map_err(Error::from_parse_error)?
+            {
+                Rsp::Error { error } => Ok(Rsp::Error { error }),
+                Rsp::OK(_) => Ok(Rsp::OK(())),
+            }
+        }
+    }
+
+    /// Typed version of `Transport::upload_file`.
+    pub fn upload_file<T>(
+        &self,
+        endpoint: &'static str,
+        fields: &[(&str, &str)],
+        file_field: &str,
+        filename: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> impl Future<Output = Result<Rsp<T>, Error>> + Send + 'static
+    where
+        T: for<'de> Deserialize<'de> + Send + 'static,
+    {
+        let c =
+            self.transport
+                .upload_file(endpoint, fields, file_field, filename, content_type, data);
+        async move {
+            Ok(serde_json::from_str(&c.await.context(NetworkError)?)
+                .map_err(Error::from_parse_error)?)
+        }
+    }
+
+    /// Streams newline-delimited JSON response, parsing each non-empty line as `T`.
+    pub fn stream_ndjson<T>(
+        &self,
+        endpoint: &'static str,
+        params: &HashMap<&str, String>,
+    ) -> impl Stream<Item = crate::YMResult<T>> + Send + 'static
+    where
+        T: for<'de> Deserialize<'de> + Send + 'static,
+    {
+        let body = self
+            .transport
+            .stream_response(endpoint, params)
+            .map(|chunk| chunk.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)));
+        let mut lines = FramedRead::new(tokio::io::stream_reader(body), LinesCodec::new());
+
+        try_stream! {
+            while let Some(line) = lines.next().await {
+                let line = line
+                    .map_err(Error::from_network_error)
+                    .context(crate::TransportError)?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                yield serde_json::from_str(&line)
+                    .map_err(Error::from_parse_error)
+                    .context(crate::TransportError)?;
+            }
         }
     }
 
@@ -185,4 +1212,9 @@ impl CallerWrapper {
 
         async move { Ok(s.await.context(NetworkError)?) }
     }
+
+    #[must_use]
+    pub fn last_headers(&self) -> Option<HashMap<String, String>> {
+        self.transport.last_response_headers()
+    }
 }
@@ -0,0 +1,18 @@
+//! Both cases share one test, as the environment is global to the test process.
+
+use yandex_money::*;
+
+#[test]
+fn yoomoney_is_default_unless_overridden() {
+    std::env::remove_var("YANDEX_MONEY_API_URL");
+
+    let caller = RemoteCaller::builder().build().unwrap();
+
+    assert_eq!(caller.addr.as_str(), "https://yoomoney.ru/");
+
+    std::env::set_var("YANDEX_MONEY_API_URL", "https://mirror.example.com/api");
+
+    let caller = RemoteCaller::builder().build().unwrap();
+
+    assert_eq!(caller.addr.as_str(), "https://mirror.example.com/");
+}
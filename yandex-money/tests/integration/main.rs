@@ -0,0 +1,94 @@
+//! Tests against a real Yandex.Money account, enabled by `sandbox-integration` feature.
+//!
+//! Required environment variables:
+//! - `TEST_TOKEN`: token with `account-info`, `operation-history` and `payment-p2p` scopes
+//! - `TEST_RECIPIENT_ACCOUNT`: account number to request test transfers to
+//! - `TEST_CLIENT_ID` and `TEST_REDIRECT_URI`: registered application for the authorization test
+//! - `TEST_REVOKE_TOKEN`: disposable token that is revoked by the revocation test, which is ignored by default
+#![cfg(feature = "sandbox-integration")]
+
+use bigdecimal::BigDecimal;
+use maplit::hashset;
+use tokio::stream::StreamExt;
+use yandex_money::*;
+
+fn env(name: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| panic!("{} must be set", name))
+}
+
+fn client() -> Client {
+    Client::new(Some(env("TEST_TOKEN")))
+}
+
+#[tokio::test]
+async fn account_info() {
+    let info = client().account_info().await.unwrap();
+
+    assert!(!info.account.is_empty());
+    assert_eq!(info.currency, "643");
+}
+
+#[tokio::test]
+async fn operation_history() {
+    let ops = client()
+        .operation_history_with_params(OperationHistoryParams::most_recent(3))
+        .take(3)
+        .collect::<YMResult<Vec<_>>>()
+        .await
+        .unwrap();
+
+    assert!(ops.len() <= 3);
+}
+
+#[tokio::test]
+async fn request_test_transfer() {
+    let recipient = env("TEST_RECIPIENT_ACCOUNT")
+        .parse()
+        .expect("TEST_RECIPIENT_ACCOUNT must be an account number");
+
+    let rsp = TestPaymentRequest::from(client().request_transfer(
+        UserId::Account(recipient),
+        RequestAmount::Total(BigDecimal::from(1)),
+        "integration test".into(),
+        "integration test".into(),
+        None,
+        false,
+        false,
+        0,
+    ))
+    .send()
+    .await
+    .unwrap();
+
+    assert!(
+        matches!(rsp, RequestPaymentResponse::Success(_)),
+        "unexpected response {:?}",
+        rsp
+    );
+    assert!(rsp.is_test_payment());
+}
+
+#[tokio::test]
+async fn authorization_url() {
+    let client = UnauthorizedClient::new(env("TEST_CLIENT_ID"), env("TEST_REDIRECT_URI"));
+
+    let (url, state) = client
+        .get_authorization_url(hashset! { AccessScope::AccountInfo })
+        .await
+        .unwrap();
+
+    assert!(url.starts_with("https://"));
+    assert!(!state.is_empty());
+}
+
+/// Revoked token cannot be reused, so this only runs on demand with a freshly issued one:
+/// `cargo test --features sandbox-integration --test integration -- --ignored revoke_token`.
+#[tokio::test]
+#[ignore]
+async fn revoke_token() {
+    let client = Client::new(Some(env("TEST_REVOKE_TOKEN")));
+    client.revoke_token().await.unwrap();
+
+    let revoked = Client::new(Some(env("TEST_REVOKE_TOKEN")));
+    assert!(revoked.account_info().await.is_err());
+}
@@ -0,0 +1,252 @@
+//! API method tests against scripted responses, enabled by `test-utils` feature.
+#![cfg(feature = "test-utils")]
+
+use std::sync::Arc;
+use yandex_money::{
+    testing::{
+        fixtures::{ACCOUNT_INFO_JSON, CARDS_LIST_JSON, REQUEST_PAYMENT_RESPONSE_JSON},
+        MockTransport,
+    },
+    *,
+};
+
+#[test]
+fn fixtures_are_parsed() {
+    testing::fixtures::parse_all();
+}
+
+#[tokio::test]
+async fn account_info_is_parsed() {
+    let transport = Arc::new(MockTransport::new());
+    transport.expect_call("api/account-info", ACCOUNT_INFO_JSON);
+
+    let info = Client::from_transport(transport.clone())
+        .account_info()
+        .await
+        .unwrap();
+
+    assert_eq!(info.account, "4100100000000000");
+    assert_eq!(
+        info.avatar_url().unwrap().as_str(),
+        "https://avatars.yandex.net/get-yapic/0/0-0/islands-200"
+    );
+    transport.verify_all_consumed();
+}
+
+#[tokio::test]
+async fn cards_list_round_trips() {
+    let transport = Arc::new(MockTransport::new());
+    transport.expect_call("api/cards-list", CARDS_LIST_JSON);
+
+    let list = Client::from_transport(transport.clone())
+        .cards_list()
+        .await
+        .unwrap();
+
+    assert_eq!(list.cards.len(), 2);
+    assert_eq!(list.cards[0].card_type, CardType::MasterCard);
+    assert_eq!(
+        list.cards[0].expire,
+        Some(chrono::NaiveDate::from_ymd(2024, 8, 31))
+    );
+    assert_eq!(list.cards[1].card_type, CardType::Mir);
+    assert_eq!(list.cards[1].expire, None);
+
+    let json = serde_json::to_string(&list).unwrap();
+    assert_eq!(
+        serde_json::from_str::<CardsListResponse>(&json).unwrap(),
+        list
+    );
+    transport.verify_all_consumed();
+}
+
+#[tokio::test]
+async fn refused_incoming_transfer_accept_keeps_details() {
+    let transport = Arc::new(MockTransport::new());
+    transport.expect_call(
+        "api/incoming-transfer-accept",
+        r#"{
+            "status": "refused",
+            "error": "illegal_param_protection_code",
+            "protection_code_attempts_available": 2
+        }"#,
+    );
+
+    let rsp = Client::from_transport(transport.clone())
+        .incoming_transfer_accept("1".into(), Some("0000".into()))
+        .await
+        .unwrap();
+
+    assert_eq!(rsp.status, IncomingTransferAcceptStatus::Refused);
+    assert_eq!(rsp.error.as_deref(), Some("illegal_param_protection_code"));
+    assert_eq!(rsp.protection_code_attempts_available, Some(2));
+    transport.verify_all_consumed();
+}
+
+#[tokio::test]
+async fn api_error_is_returned() {
+    let transport = Arc::new(MockTransport::new());
+    transport.expect_call(
+        "api/operation-details",
+        r#"{"error": "illegal_param_operation_id"}"#,
+    );
+
+    let e = Client::from_transport(transport.clone())
+        .operation_details("1".into())
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        e.yandex_code(),
+        Some(YandexErrorCode::IllegalParamOperationId)
+    );
+    transport.verify_all_consumed();
+}
+
+#[tokio::test]
+async fn invalid_token_is_auth_failure() {
+    let transport = Arc::new(MockTransport::new());
+    transport.expect_call("api/account-info", r#"{"error": "invalid_token"}"#);
+
+    let e = Client::from_transport(transport.clone())
+        .account_info()
+        .await
+        .unwrap_err();
+
+    assert!(e.is_auth_failure());
+    assert!(!e.is_retryable());
+    transport.verify_all_consumed();
+}
+
+#[tokio::test]
+async fn unauthorized_status_is_auth_failure() {
+    let transport = Arc::new(MockTransport::new());
+    transport.expect_http_error("api/account-info", http::StatusCode::UNAUTHORIZED, "");
+
+    let e = Client::from_transport(transport.clone())
+        .account_info()
+        .await
+        .unwrap_err();
+
+    assert!(e.is_auth_failure());
+    assert!(!e.is_retryable());
+    assert!(!e.is_transient());
+    transport.verify_all_consumed();
+}
+
+/// Token store with fixed refresh credentials, sharing the last saved token with the test.
+#[derive(Default)]
+struct MemoryTokenStore {
+    token: Arc<parking_lot::Mutex<Option<String>>>,
+}
+
+#[async_trait::async_trait]
+impl TokenStore for MemoryTokenStore {
+    async fn load(&self) -> YMResult<Option<SecureString>> {
+        Ok(self.token.lock().as_deref().map(SecureString::from))
+    }
+
+    async fn save(&self, token: &str) -> YMResult<()> {
+        *self.token.lock() = Some(token.to_string());
+        Ok(())
+    }
+
+    async fn clear(&self) -> YMResult<()> {
+        *self.token.lock() = None;
+        Ok(())
+    }
+
+    async fn load_refresh_credentials(&self) -> YMResult<Option<RefreshCredentials>> {
+        Ok(Some(RefreshCredentials {
+            client_id: "client".into(),
+            client_secret: "secret".into(),
+            refresh_token: "refresh".into(),
+        }))
+    }
+
+    async fn save_refresh_credentials(&self, _credentials: &RefreshCredentials) -> YMResult<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn rejected_token_is_refreshed_and_call_retried() {
+    let transport = Arc::new(MockTransport::new());
+    transport
+        .expect_http_error("api/account-info", http::StatusCode::UNAUTHORIZED, "")
+        .expect_call("oauth/token", r#"{"access_token": "new-token"}"#)
+        .expect_call("api/account-info", ACCOUNT_INFO_JSON);
+    let store = MemoryTokenStore::default();
+    let saved_token = store.token.clone();
+
+    let info = Client::from_transport(transport.clone())
+        .auto_refresh_token(Box::new(store))
+        .account_info()
+        .await
+        .unwrap();
+
+    assert_eq!(info.account, "4100100000000000");
+    assert_eq!(saved_token.lock().as_deref(), Some("new-token"));
+    transport.verify_all_consumed();
+}
+
+#[test]
+fn transfer_builder_rejects_expiration_shorter_than_a_day() {
+    let client = Client::from_transport(Arc::new(MockTransport::new()));
+    let builder = || {
+        client
+            .transfer_builder()
+            .to(UserId::Account(4_100_100_000_000_001))
+            .amount(RequestAmount::Total(bigdecimal::BigDecimal::from(1)))
+    };
+
+    for period in &[
+        chrono::Duration::zero(),
+        chrono::Duration::hours(-12),
+        chrono::Duration::hours(23),
+    ] {
+        assert!(builder().expires_in(*period).build(&client).is_err());
+    }
+    assert!(builder().build(&client).is_ok());
+    assert!(builder().expire_period(1).build(&client).is_ok());
+}
+
+#[tokio::test]
+async fn test_payment_is_reported() {
+    let transport = Arc::new(MockTransport::new());
+    transport
+        .expect_call("api/request-payment", REQUEST_PAYMENT_RESPONSE_JSON)
+        .expect_call(
+            "api/request-payment",
+            &REQUEST_PAYMENT_RESPONSE_JSON.replacen(
+                r#""status": "success","#,
+                r#""status": "success", "test_payment": true,"#,
+                1,
+            ),
+        );
+    let client = Client::from_transport(transport.clone());
+
+    let rsp = client
+        .request_shop_payment("337".into(), Default::default())
+        .send()
+        .await
+        .unwrap();
+    assert!(!rsp.is_test_payment());
+
+    let rsp =
+        TestPaymentRequest::from(client.request_shop_payment("337".into(), Default::default()))
+            .send()
+            .await
+            .unwrap();
+    assert!(rsp.is_test_payment());
+    transport.verify_all_consumed();
+}
+
+#[tokio::test]
+#[should_panic(expected = "expected calls were not made")]
+async fn unconsumed_calls_are_reported() {
+    let transport = MockTransport::new();
+    transport.expect_call("api/account-info", ACCOUNT_INFO_JSON);
+
+    transport.verify_all_consumed();
+}
@@ -0,0 +1,208 @@
+//! Offline tests of model parsing and helpers.
+
+use yandex_money::*;
+
+#[test]
+fn year_month_rejects_invalid_month_on_deserialization() {
+    assert!(serde_json::from_str::<YearMonth>(r#"{"year": 2020, "month": 13}"#).is_err());
+
+    let period = serde_json::from_str::<YearMonth>(r#"{"year": 2020, "month": 12}"#).unwrap();
+    let (from, till) = period.date_range().unwrap();
+    assert_eq!(from.to_rfc3339(), "2020-12-01T00:00:00+00:00");
+    assert_eq!(till.to_rfc3339(), "2021-01-01T00:00:00+00:00");
+}
+
+#[test]
+fn operation_details_phone_number_survives_round_trip() {
+    let json = r#"{
+        "operation_id": "1234568",
+        "status": "success",
+        "pattern_id": "phone-topup",
+        "direction": "out",
+        "amount": 100.00,
+        "datetime": "2020-03-15T14:27:00Z",
+        "title": "Пополнение телефона",
+        "operation_type": "payment-shop",
+        "payment_parameters": {"phone-number": "79001234567"}
+    }"#;
+
+    let details = OperationDetails::from_json(json).unwrap();
+    assert!(details.is_mobile_topup());
+    assert_eq!(details.phone_number.unwrap().to_string(), "+79001234567");
+
+    let details = OperationDetails::from_json(&details.to_json()).unwrap();
+    assert_eq!(details.phone_number.unwrap().to_string(), "+79001234567");
+}
+
+#[test]
+fn callback_uris_accept_same_local_http_hosts() {
+    let cases = [
+        ("https://example.com/callback", true),
+        ("http://localhost:8080/callback", true),
+        ("http://127.0.0.1:8080/callback", true),
+        ("http://example.com/callback", false),
+        ("ftp://localhost/callback", false),
+        ("not a uri", false),
+    ];
+
+    for &(uri, valid) in &cases {
+        assert_eq!(Secure3DData::new(uri, uri).is_ok(), valid, "{}", uri);
+
+        let client = UnauthorizedClient::new("client".into(), uri.into());
+        assert_eq!(client.validate_redirect_uri().is_ok(), valid, "{}", uri);
+    }
+}
+
+fn operation(id: &str, status: &str, datetime: &str) -> Operation {
+    serde_json::from_value(serde_json::json!({
+        "operation_id": id,
+        "status": status,
+        "datetime": datetime,
+        "title": "Test operation",
+        "pattern_id": "p2p",
+        "direction": "out",
+        "amount": "10.00",
+        "type": "outgoing-transfer",
+    }))
+    .unwrap()
+}
+
+#[test]
+fn merge_operations_prefers_fresh_status() {
+    let cached = vec![
+        operation("1", "in_progress", "2020-03-15T10:00:00Z"),
+        operation("2", "success", "2020-03-15T11:00:00Z"),
+    ];
+    let fresh = vec![
+        operation("1", "success", "2020-03-15T10:00:00Z"),
+        operation("3", "in_progress", "2020-03-15T12:00:00Z"),
+    ];
+
+    let merged = merge_operations(cached, fresh);
+
+    let ids = merged
+        .iter()
+        .map(|op| op.operation_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["3", "2", "1"]);
+    assert!(matches!(merged[2].status, OperationStatus::Success));
+    assert!(matches!(merged[0].status, OperationStatus::InProgress));
+
+    // Stale cached status must not override a fresh one either
+    let merged = merge_operations(
+        vec![operation("1", "success", "2020-03-15T10:00:00Z")],
+        vec![operation("1", "in_progress", "2020-03-15T10:00:00Z")],
+    );
+    assert_eq!(merged.len(), 1);
+    assert!(matches!(merged[0].status, OperationStatus::InProgress));
+}
+
+#[test]
+fn secure_string_is_never_formatted() {
+    let secret = SecureString::from("hunter2-secret");
+
+    for formatted in &[
+        format!("{}", secret),
+        format!("{:?}", secret),
+        format!("{:#?}", secret),
+        format!("{:?}", Some(&secret)),
+    ] {
+        assert!(!formatted.contains("hunter2-secret"), "{}", formatted);
+    }
+    assert_eq!(&*secret, "hunter2-secret");
+}
+
+#[test]
+fn yandex_error_codes_are_classified_exhaustively() {
+    const CODES: &[&str] = &[
+        "illegal_params",
+        "illegal_param_label",
+        "illegal_param_to",
+        "illegal_param_amount",
+        "illegal_param_amount_due",
+        "illegal_param_comment",
+        "illegal_param_message",
+        "illegal_param_expire_period",
+        "illegal_param_csc",
+        "illegal_param_ext_auth_success_uri",
+        "illegal_param_ext_auth_fail_uri",
+        "illegal_param_operation_id",
+        "illegal_param_type",
+        "illegal_param_start_record",
+        "illegal_param_records",
+        "illegal_param_from",
+        "illegal_param_till",
+        "not_enough_funds",
+        "payment_refused",
+        "payee_not_found",
+        "authorization_reject",
+        "limit_exceeded",
+        "account_blocked",
+        "ext_action_required",
+        "contract_not_found",
+        "money_source_not_available",
+        "technical_error",
+        "invalid_request",
+        "unauthorized_client",
+        "invalid_grant",
+        "invalid_scope",
+        "unauthorized",
+        "invalid_token",
+        "illegal_param_token",
+        "some_future_error",
+    ];
+
+    for &code in CODES {
+        let error = Error::YandexError {
+            description: code.into(),
+        };
+
+        assert_eq!(
+            error.is_insufficient_funds(),
+            ["not_enough_funds", "limit_exceeded"].contains(&code),
+            "{}",
+            code
+        );
+        assert_eq!(
+            error.is_recipient_error(),
+            ["payee_not_found", "illegal_param_to"].contains(&code),
+            "{}",
+            code
+        );
+    }
+
+    let error = Error::InvalidParams {
+        description: "not_enough_funds".into(),
+    };
+    assert!(!error.is_insufficient_funds());
+    assert!(!error.is_recipient_error());
+}
+
+#[test]
+fn operation_details_amounts_parse_from_strings_and_numbers() {
+    let details = |amount: &str| {
+        OperationDetails::from_json(&format!(
+            r#"{{
+                "operation_id": "1234567",
+                "status": "success",
+                "direction": "out",
+                "amount": {0},
+                "amount_due": {0},
+                "fee": {0},
+                "datetime": "2020-03-15T14:27:00Z",
+                "title": "Test operation",
+                "operation_type": "outgoing-transfer"
+            }}"#,
+            amount
+        ))
+        .unwrap()
+    };
+
+    let expected = "100.50".parse::<bigdecimal::BigDecimal>().unwrap();
+    for amount in &[r#""100.50""#, "100.50"] {
+        let details = details(amount);
+        assert_eq!(details.amount, expected, "{}", amount);
+        assert_eq!(details.amount_due.as_ref(), Some(&expected), "{}", amount);
+        assert_eq!(details.fee.as_ref(), Some(&expected), "{}", amount);
+    }
+}
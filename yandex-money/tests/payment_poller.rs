@@ -0,0 +1,73 @@
+//! `PaymentPoller` tests against scripted responses, enabled by `test-utils` feature.
+#![cfg(feature = "test-utils")]
+
+use std::{sync::Arc, time::Duration};
+use yandex_money::{
+    testing::{fixtures::PROCESS_PAYMENT_RESPONSE_JSON, MockTransport},
+    *,
+};
+
+const IN_PROGRESS_JSON: &str = r#"{"status": "in_progress", "next_retry": 5000}"#;
+
+fn poller(transport: Arc<MockTransport>, max_attempts: u32) -> PaymentPoller<Client> {
+    PaymentPoller::builder()
+        .api(Arc::new(Client::from_transport(transport)))
+        .request_id("313533313430323037355f6d6f636b")
+        .money_source(ProcessPaymentMoneySource::Wallet)
+        .poll_interval(Duration::from_millis(1))
+        .max_attempts(max_attempts)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn polls_until_success() {
+    let transport = Arc::new(MockTransport::new());
+    transport
+        .expect_call("api/process-payment", IN_PROGRESS_JSON)
+        .expect_call("api/process-payment", IN_PROGRESS_JSON)
+        .expect_call("api/process-payment", PROCESS_PAYMENT_RESPONSE_JSON);
+
+    let rsp = poller(transport.clone(), 5)
+        .poll_until_terminal()
+        .await
+        .unwrap();
+
+    assert!(matches!(rsp, ProcessPaymentResponse::Success(_)));
+    transport.verify_all_consumed();
+}
+
+#[tokio::test]
+async fn refusal_is_terminal() {
+    let transport = Arc::new(MockTransport::new());
+    transport
+        .expect_call("api/process-payment", IN_PROGRESS_JSON)
+        .expect_call(
+            "api/process-payment",
+            r#"{"status": "refused", "error": "not_enough_funds"}"#,
+        );
+
+    let e = poller(transport.clone(), 5)
+        .poll_until_terminal()
+        .await
+        .unwrap_err();
+
+    assert_eq!(e.yandex_code(), Some(YandexErrorCode::NotEnoughFunds));
+    transport.verify_all_consumed();
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let transport = Arc::new(MockTransport::new());
+    transport
+        .expect_call("api/process-payment", IN_PROGRESS_JSON)
+        .expect_call("api/process-payment", IN_PROGRESS_JSON);
+
+    let e = poller(transport.clone(), 2)
+        .poll_until_terminal()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(e, Error::PaymentPollExhausted { attempts: 2, .. }));
+    transport.verify_all_consumed();
+}
@@ -0,0 +1,155 @@
+use http::StatusCode;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use yandex_money::*;
+
+/// Fails the first `failures` calls with `error`, then returns `{}`.
+#[derive(Debug)]
+struct FlakyTransport {
+    failures: u32,
+    error: fn() -> StdError,
+    calls: AtomicU32,
+}
+
+impl FlakyTransport {
+    fn new(failures: u32, error: fn() -> StdError) -> Arc<Self> {
+        Arc::new(Self {
+            failures,
+            error,
+            calls: AtomicU32::new(0),
+        })
+    }
+
+    fn calls(&self) -> u32 {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+impl Transport for FlakyTransport {
+    fn call(
+        &self,
+        _endpoint: &'static str,
+        _params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        let res = if call < self.failures {
+            Err((self.error)())
+        } else {
+            Ok("{}".to_string())
+        };
+
+        Box::pin(async move { res })
+    }
+
+    fn get_redirect(
+        &self,
+        _endpoint: &'static str,
+        _params: &HashMap<&str, String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String, StdError>> + Send + 'static>> {
+        Box::pin(async { Err(StdError::from("not supported")) })
+    }
+}
+
+fn connection_reset() -> StdError {
+    std::io::Error::from(std::io::ErrorKind::ConnectionReset).into()
+}
+
+fn unauthorized() -> StdError {
+    HttpStatusError {
+        status: StatusCode::UNAUTHORIZED,
+        body: String::new(),
+    }
+    .into()
+}
+
+fn policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_delay: Duration::from_millis(1),
+        multiplier: 2.0,
+        jitter: 0.5,
+    }
+}
+
+async fn call(transport: Arc<FlakyTransport>, policy: RetryPolicy) -> Result<(), Error> {
+    CallerWrapper::new(transport)
+        .with_retry(policy)
+        .call::<HashMap<String, String>>("api/account-info", &HashMap::new())
+        .await
+        .map(drop)
+        .map_err(|source| Error::TransportError { source })
+}
+
+#[tokio::test]
+async fn succeeds_after_network_errors() {
+    let transport = FlakyTransport::new(2, connection_reset);
+
+    call(transport.clone(), policy(3)).await.unwrap();
+
+    assert_eq!(transport.calls(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let transport = FlakyTransport::new(3, connection_reset);
+
+    let e = call(transport.clone(), policy(3)).await.unwrap_err();
+
+    assert!(e.is_transient());
+    assert_eq!(transport.calls(), 3);
+}
+
+#[tokio::test]
+async fn does_not_retry_client_errors() {
+    let transport = FlakyTransport::new(1, unauthorized);
+
+    let e = call(transport.clone(), policy(3)).await.unwrap_err();
+
+    assert!(!e.is_retryable());
+    assert!(!e.is_transient());
+    assert_eq!(transport.calls(), 1);
+}
+
+#[tokio::test]
+async fn no_retry_makes_single_attempt() {
+    let transport = FlakyTransport::new(1, connection_reset);
+
+    call(transport.clone(), RetryPolicy::no_retry())
+        .await
+        .unwrap_err();
+
+    assert_eq!(transport.calls(), 1);
+}
+
+#[test]
+fn invalid_base_url_is_not_transient() {
+    for url in &["not a url", "mailto:a@b"] {
+        let source = Client::builder().with_base_url(*url).build().err().unwrap();
+        assert!(
+            source
+                .to_string()
+                .starts_with("Invalid client configuration"),
+            "{}",
+            url
+        );
+
+        let e = Error::TransportError { source };
+        assert!(!e.is_transient(), "{}", url);
+        assert!(!e.is_retryable(), "{}", url);
+    }
+
+    let source = Client::builder()
+        .add_header("bad header", "value")
+        .build()
+        .err()
+        .unwrap();
+    assert!(!Error::TransportError { source }.is_transient());
+}
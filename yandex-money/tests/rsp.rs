@@ -0,0 +1,48 @@
+use yandex_money::*;
+
+fn ok(v: u32) -> Rsp<u32> {
+    Rsp::OK(v)
+}
+
+fn error() -> Rsp<u32> {
+    Rsp::Error {
+        error: "illegal_params".into(),
+    }
+}
+
+#[test]
+fn map() {
+    assert_eq!(ok(2).map(|v| v * 2).ok(), Some(4));
+    assert_eq!(
+        error().map(|v| v * 2).err().as_deref(),
+        Some("illegal_params")
+    );
+}
+
+#[test]
+fn and_then() {
+    assert_eq!(ok(2).and_then(|v| Rsp::OK(v + 1)).ok(), Some(3));
+    assert_eq!(
+        ok(2)
+            .and_then(|_| -> Rsp<u32> {
+                Rsp::Error {
+                    error: "payment_refused".into(),
+                }
+            })
+            .err()
+            .as_deref(),
+        Some("payment_refused")
+    );
+    assert_eq!(
+        error().and_then(|v| Rsp::OK(v + 1)).err().as_deref(),
+        Some("illegal_params")
+    );
+}
+
+#[test]
+fn ok_and_err() {
+    assert_eq!(ok(1).ok(), Some(1));
+    assert_eq!(ok(1).err(), None);
+    assert_eq!(error().ok(), None);
+    assert_eq!(error().err().as_deref(), Some("illegal_params"));
+}
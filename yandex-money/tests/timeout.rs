@@ -0,0 +1,22 @@
+use std::{net::TcpListener, time::Duration};
+use url::Url;
+use yandex_money::*;
+
+#[tokio::test]
+async fn request_timeout_fails_with_network_error() {
+    // Connections are queued by the OS but never answered
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+
+    let client = Client::builder()
+        .token("token")
+        .base_url(base_url)
+        .request_timeout(Duration::from_millis(1))
+        .build()
+        .unwrap();
+
+    let e = client.account_info().await.unwrap_err();
+
+    assert!(e.is_transient());
+    assert!(e.to_string().contains("Network error"), "{}", e);
+}